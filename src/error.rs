@@ -0,0 +1,78 @@
+use std::{fmt::Display, path::PathBuf, process::ExitStatus};
+
+use crate::Solution;
+
+/// Everything that can go wrong while writing a problem, running LKH, or reading its output.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LkhError {
+  /// Reading or writing a file (or pipe) failed
+  Io(std::io::Error),
+  /// The LKH executable could not be found or started
+  SolverNotFound { path: PathBuf },
+  /// LKH ran but exited unsuccessfully
+  SolverFailed { status: ExitStatus, stdout: String, stderr: String },
+  /// The solve was stopped through a [`crate::solver::CancelHandle`] (or LKH was killed
+  /// from outside). `partial` is the best tour LKH had written so far, if any.
+  Cancelled { partial: Option<Box<Solution>> },
+  /// The solve was stopped at its deadline. `partial` is the best tour LKH had written
+  /// so far, if any.
+  TimedOut { partial: Option<Box<Solution>> },
+  /// A tour (or solution) file written by LKH could not be understood. `line` is 1-indexed,
+  /// and `None` when no single line is at fault (e.g. the tour misses the depot).
+  MalformedTour { line: Option<usize>, message: String },
+  /// LKH returned a solution breaking a constraint of the problem, e.g. a precedence.
+  /// `solution` is the solution as LKH returned it.
+  Infeasible { message: String, solution: Box<Solution> },
+  /// The problem is inconsistent, or uses something LKH cannot express
+  InvalidProblem(String),
+  /// A parameter value is outside the range LKH accepts for its keyword
+  InvalidParameter { keyword: &'static str, message: String },
+}
+
+pub type Result<T> = std::result::Result<T, LkhError>;
+
+impl LkhError {
+  pub(crate) fn malformed_tour(line: usize, message: impl Into<String>) -> Self {
+    Self::MalformedTour { line: Some(line), message: message.into() }
+  }
+
+  /// As [`LkhError::malformed_tour`], for a file at fault as a whole.
+  pub(crate) fn malformed_solution(message: impl Into<String>) -> Self {
+    Self::MalformedTour { line: None, message: message.into() }
+  }
+
+  pub(crate) fn invalid_problem(message: impl Into<String>) -> Self {
+    Self::InvalidProblem(message.into())
+  }
+}
+
+impl Display for LkhError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Io(e) => write!(f, "I/O error: {e}"),
+      Self::SolverNotFound { path } => write!(f, "LKH executable not found: {}", path.display()),
+      Self::SolverFailed { status, stderr, .. } => write!(f, "LKH exited with {status}: {}", stderr.trim()),
+      Self::Cancelled { .. } => write!(f, "solve was cancelled"),
+      Self::TimedOut { .. } => write!(f, "solve timed out"),
+      Self::MalformedTour { line: Some(line), message } => write!(f, "malformed tour (line {line}): {message}"),
+      Self::MalformedTour { line: None, message } => write!(f, "malformed tour: {message}"),
+      Self::Infeasible { message, .. } => write!(f, "infeasible solution: {message}"),
+      Self::InvalidProblem(message) => write!(f, "invalid problem: {message}"),
+      Self::InvalidParameter { keyword, message } => write!(f, "invalid parameter {keyword}: {message}"),
+    }
+  }
+}
+
+impl std::error::Error for LkhError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Self::Io(e) => Some(e),
+      _ => None
+    }
+  }
+}
+
+impl From<std::io::Error> for LkhError {
+  fn from(e: std::io::Error) -> Self { Self::Io(e) }
+}
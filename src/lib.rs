@@ -1,101 +1,75 @@
 #![allow(dead_code)]
-use std::{path::PathBuf, process::Command};
-
 pub type Node = u32;
 pub type Edge = [Node; 2];
 pub type SpatialCoordinate<const N: usize> = [f32; N];
 
-mod problem; use memory_file::tmp_file;
+pub mod problem;
 use problem::{header::{EdgeFormat, EdgeWeightKind, ProblemKind}, *};
 
-use crate::tour::*;
-mod parameter;
+use parameter::Parameters;
+pub use error::{LkhError, Result};
+pub use solver::{Solution, Solver};
+mod error;
+pub mod parameter;
 pub mod memory_file;
+pub mod report;
+pub mod solver;
 pub mod util; 
 pub mod tour;
-
-fn solve_files(
-  tour_file: &PathBuf,
-  parameter_file: &PathBuf
-) -> std::io::Result<Vec<Node>> {
-  println!("Solving...");
-
-  let output = 
-    Command::new("lkh")
-      .arg(parameter_file)
-      .output()?;
-
-  println!("Output: {:?}", output);
-
-  TourData::from_file(tour_file)?
-    .parse()
-    .tour()
-}
-
-use std::io::Write;
+pub mod variant;
 
 pub fn solve_toolpath(
   endpoint_pairs: &[ [[f32; 2]; 2] ]
-) -> std::io::Result<Vec<Node>> {
-  let problem_file = {
-    let p = 
-      Problem::with(Vec::with_capacity(4096))
-        .header()
-          .problem_kind(ProblemKind::TSP)?
-          .dimension(endpoint_pairs.len() as u32 * 2)?
-          .edge_weight_kind(EdgeWeightKind::EUC_2D)?
-          .edge_data_format(EdgeFormat::ADJ_LIST)?
-        .coords()
-          .begin_node_coordinates()?
-          .write_coordinates(
-            endpoint_pairs.iter().flat_map(|p| p).map(|[a, b]| [100. * a, 100. * b])
+) -> Result<Solution> {
+  let problem = 
+    Problem::with(Vec::with_capacity(4096))
+      .header()
+        .problem_kind(ProblemKind::TSP)?
+        .dimension(endpoint_pairs.len() as u32 * 2)?
+        .edge_weight_kind(EdgeWeightKind::EUC_2D)?
+        .edge_data_format(EdgeFormat::ADJ_LIST)?
+      .coords()
+        .begin_node_coordinates()?
+        .write_coordinates(
+          endpoint_pairs.iter().flat_map(|p| p).map(|[a, b]| [100. * a, 100. * b])
+        )?
+      .edges()
+        .fixed_edges()
+          .begin_fixed_edges()?
+          .write_fixed_edges(
+            (0..endpoint_pairs.len() as u32)
+              .map(|i| i + 1)
+              .map(|i| 2 * i)
+              .map(|i| [i - 1, i])
           )?
-        .edges()
-          .fixed_edges()
-            .begin_fixed_edges()?
-            .write_fixed_edges(
-              (0..endpoint_pairs.len() as u32)
-                .map(|i| i + 1)
-                .map(|i| 2 * i)
-                .map(|i| [i - 1, i])
-            )?
-        .finish();
+          .end_fixed_edges()?
+      .finish();
 
-      let t = tmp_file("tsp");
-      std::fs::write(&t, p)?;
-      t
-  };
-
-  let tour_file = tmp_file("tour");
-
-  let parameter_file = {
-    let mut m = Vec::with_capacity(4096);
-    parameter::problem_file(&mut m, problem_file.to_str().unwrap())?;
-    // writeln!(&mut m, "MOVE_TYPE = 5")?;
-    // writeln!(&mut m, "PATCHING_C = 3")?;
-    // writeln!(&mut m, "PATCHING_A = 2")?;
-    // writeln!(&mut m, "RUNS = 10")?;
-    writeln!(&mut m, "POPULATION_SIZE = 256")?;
-    writeln!(&mut m, "TOUR_FILE = {}", tour_file.to_str().unwrap())?;
-
-    m.flush().unwrap();
-
-    let p = tmp_file("par");
-    println!("parameter file path: {:?}", p.to_str());
-    println!("parameter file contents: {}", String::from_utf8(m.clone()).unwrap());
+  Solver::new()?.solve_with(problem, &Parameters::new().population_size(256))
+}
 
-    std::fs::write(&p, m).unwrap();
-    p
-  };
+/// Solves the asymmetric TSP with `costs.get(i, j)` the cost of going from `i` to `j`,
+/// using the LKH found by [`Solver::new`].
+pub fn solve_atsp(
+  costs: &matrix::Matrix
+) -> Result<variant::atsp::AtspSolution> {
+  let solver = Solver::new()?;
+  solver.solve_atsp(costs, variant::atsp::AtspTransform::Native, solver.default_parameters())
+}
 
-  solve_files(&tour_file, &parameter_file)
+/// A cycle through every node of the undirected graph `edges`, if LKH finds one, using the
+/// LKH found by [`Solver::new`].
+pub fn find_hamiltonian_cycle(
+  edges: &[Edge]
+) -> Result<Option<Vec<Node>>> {
+  let solver = Solver::new()?;
+  solver.find_hamiltonian_cycle(edges, solver.default_parameters())
 }
 
 #[cfg(test)]
 mod test {
-  use std::io::Write;
   use rand::{thread_rng, Rng};
-  use crate::{header::{EdgeFormat, EdgeWeightKind, ProblemKind}, memory_file::tmp_file, parameter, solve_toolpath, Problem};
+  use crate::{header::{EdgeFormat, EdgeWeightKind, ProblemKind}, parameter::Parameters, solve_toolpath, Problem, Solver};
 
   #[test]
   fn xx() {
@@ -118,61 +92,36 @@ mod test {
     const DIM: u32 = 10;
 
     (|| {
-      let problem_file = {
-        let m = 
-          Problem::with(Vec::with_capacity(4096))
-            .header()
-              .name("test")?
-              .problem_kind(ProblemKind::TSP)?
-              .comment("test comment")?
-              .dimension(DIM)?
-              .edge_weight_kind(EdgeWeightKind::EUC_2D)?
-              .edge_data_format(EdgeFormat::ADJ_LIST)?
-            .coords()
-              .begin_node_coordinates()?
-              .write_coordinates(
-                (0..DIM).map(|_| thread_rng().gen::<[f32; 2]>()).map(|[a, b]| [100. * a, 100. * b])
-              )?
-            // .edges()
-            //   .begin_adjacency()?
-            //   .write_adjacency_edges(
-            //     (0..DIM)
-            //       .flat_map(|i| (0..DIM).filter(move |j| *j != i).map(move |j| [i, j]))
-            //   )?
-            .finish();
-
-        let p = tmp_file("tsp");
-        std::fs::write(&p, m)?;
-        p
-      };
-
-      let tour_file = tmp_file("tour");
-
-      let parameter_file = {
-        let mut m = Vec::with_capacity(4096);
-        parameter::problem_file(&mut m, problem_file.to_str().unwrap())?;
-        // writeln!(&mut m, "MOVE_TYPE = 5")?;
-        // writeln!(&mut m, "PATCHING_C = 3")?;
-        // writeln!(&mut m, "PATCHING_A = 2")?;
-        writeln!(&mut m, "RUNS = 2")?;
-        writeln!(&mut m, "POPULATION_SIZE = 512")?;
-        writeln!(&mut m, "TOUR_FILE = {}", tour_file.to_str().unwrap())?;
-
-        m.flush().unwrap();
-
-        let p = tmp_file("par");
-        println!("parameter file path: {:?}", p.to_str());
-        println!("parameter file contents: {}", String::from_utf8(m.clone()).unwrap());
-
-        std::fs::write(&p, m).unwrap();
-        p
-      };
-
-      let soln = super::solve_files(&tour_file, &parameter_file).unwrap();
+      let problem = 
+        Problem::with(Vec::with_capacity(4096))
+          .header()
+            .name("test")?
+            .problem_kind(ProblemKind::TSP)?
+            .comment("test comment")?
+            .dimension(DIM)?
+            .edge_weight_kind(EdgeWeightKind::EUC_2D)?
+            .edge_data_format(EdgeFormat::ADJ_LIST)?
+          .coords()
+            .begin_node_coordinates()?
+            .write_coordinates(
+              (0..DIM).map(|_| thread_rng().gen::<[f32; 2]>()).map(|[a, b]| [100. * a, 100. * b])
+            )?
+          // .edges()
+          //   .begin_adjacency()?
+          //   .write_adjacency_edges(
+          //     (0..DIM)
+          //       .flat_map(|i| (0..DIM).filter(move |j| *j != i).map(move |j| [i, j]))
+          //   )?
+          .finish();
+
+      let soln = 
+        Solver::new()?
+          .solve_with(problem, &Parameters::new().runs(2).population_size(512))
+          .unwrap();
 
       println!("soln: {:?}", soln);
 
-      std::io::Result::Ok(())
+      crate::Result::Ok(())
     })().unwrap();
   }
 }
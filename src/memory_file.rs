@@ -1,4 +1,4 @@
-use std::{ffi::CStr, fs::OpenOptions, io::{Error, Write}, path::PathBuf};
+use std::{ffi::CStr, fs::OpenOptions, io::{Error, Write}, path::{Path, PathBuf}};
 use memmap::MmapMut;
 use zeroize::Zeroize;
 
@@ -83,10 +83,30 @@ impl Write for MemoryFile {
   }
 }
 
+/// A temporary file path which is removed (if it exists) when dropped.
+#[derive(Debug)]
+pub struct TempFile(PathBuf);
+
+impl TempFile {
+  pub fn new(directory: &Path, extension: &str) -> Self { Self(tmp_file_in(directory, extension)) }
+  pub fn path(&self) -> &PathBuf { &self.0 }
+}
+
+impl Drop for TempFile {
+  fn drop(&mut self) {
+    // nothing can be done about a file that cannot be removed here
+    let _ = std::fs::remove_file(&self.0);
+  }
+}
+
 use rand::{distributions::Alphanumeric, Rng};
 
 pub fn tmp_file(extension: &str) -> PathBuf {
-  std::env::temp_dir().join(
+  tmp_file_in(&std::env::temp_dir(), extension)
+}
+
+pub fn tmp_file_in(directory: &Path, extension: &str) -> PathBuf {
+  directory.join(
     &format!(
       "lkh_{}.tmp.{}", 
       {
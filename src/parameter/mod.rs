@@ -1,8 +1,751 @@
-use std::io::Write;
+use std::{fmt::Display, io::Write, path::{Path, PathBuf}};
 
-pub fn problem_file(
-  w: &mut impl Write,
-  path: &str
-) -> std::io::Result<()> {
-  writeln!(w, "PROBLEM_FILE = {path}")
+use crate::{LkhError, Node};
+
+/// The contents of an LKH parameter file.
+///
+/// Every keyword understood by LKH-2 and LKH-3 has a typed setter. Keywords that are
+/// never set are omitted from the file, leaving LKH to pick its own default. Values are
+/// range checked when the file is written (see [`Parameters::validate`]).
+#[derive(Debug, Clone, Default)]
+pub struct Parameters {
+  problem_file: Option<PathBuf>,
+  tour_file: Option<PathBuf>,
+  output_tour_file: Option<PathBuf>,
+  initial_tour_file: Option<PathBuf>,
+  input_tour_file: Option<PathBuf>,
+  merge_tour_files: Vec<PathBuf>,
+  candidate_files: Vec<PathBuf>,
+  edge_files: Vec<PathBuf>,
+  pi_file: Option<PathBuf>,
+  mtsp_solution_file: Option<PathBuf>,
+  sintef_solution_file: Option<PathBuf>,
+  subproblem_tour_file: Option<PathBuf>,
+
+  ascent_candidates: Option<u32>,
+  backbone_trials: Option<u32>,
+  backtracking: Option<bool>,
+  bwtsp: Option<Bwtsp>,
+  candidate_set_kind: Option<CandidateSetKind>,
+  depot: Option<Node>,
+  excess: Option<f64>,
+  extra_candidates: Option<(u32, bool)>,
+  extra_candidate_set_kind: Option<CandidateSetKind>,
+  gain23: Option<bool>,
+  gain_criterion: Option<bool>,
+  initial_period: Option<u32>,
+  initial_step_size: Option<u32>,
+  initial_tour_algorithm: Option<InitialTourAlgorithm>,
+  initial_tour_fraction: Option<f64>,
+  kick_type: Option<u32>,
+  kicks: Option<u32>,
+  makespan: Option<bool>,
+  max_breadth: Option<u32>,
+  max_candidates: Option<(u32, bool)>,
+  max_swaps: Option<u32>,
+  max_trials: Option<u32>,
+  move_type: Option<(u32, bool)>,
+  mtsp_min_size: Option<u32>,
+  mtsp_max_size: Option<u32>,
+  mtsp_objective: Option<MtspObjective>,
+  nonsequential_move_type: Option<u32>,
+  optimum: Option<i64>,
+  patching_a: Option<Patching>,
+  patching_c: Option<Patching>,
+  popmusic_initial_tour: Option<bool>,
+  popmusic_max_neighbors: Option<u32>,
+  popmusic_sample_size: Option<u32>,
+  popmusic_solutions: Option<u32>,
+  popmusic_trials: Option<u32>,
+  population_size: Option<u32>,
+  precision: Option<u32>,
+  recombination: Option<Recombination>,
+  restricted_search: Option<bool>,
+  runs: Option<u32>,
+  salesmen: Option<u32>,
+  scale: Option<u32>,
+  seed: Option<u32>,
+  special: bool,
+  stop_at_optimum: Option<bool>,
+  subgradient: Option<bool>,
+  subproblem_size: Option<Subproblem>,
+  subsequent_move_type: Option<(u32, bool)>,
+  subsequent_patching: Option<bool>,
+  time_limit: Option<f64>,
+  total_time_limit: Option<f64>,
+  trace_level: Option<u32>,
+  vehicles: Option<u32>,
+}
+
+/// Specifies the type of candidate set to be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+#[non_exhaustive]
+pub enum CandidateSetKind {
+  /// Candidates are chosen by their alpha-nearness (the LKH default)
+  ALPHA,
+  /// Candidates are taken from a Delaunay graph, optionally without alpha-ranking (`PURE`)
+  DELAUNAY { pure: bool },
+  /// Candidates are the nearest neighbors of a node
+  NEAREST_NEIGHBOR,
+  /// Candidates are the quadrant neighbors of a node
+  QUADRANT,
+  /// Candidates are found using the POPMUSIC metaheuristic
+  POPMUSIC,
+}
+
+impl Display for CandidateSetKind {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::ALPHA => write!(f, "ALPHA"),
+      Self::DELAUNAY { pure: false } => write!(f, "DELAUNAY"),
+      Self::DELAUNAY { pure: true } => write!(f, "DELAUNAY PURE"),
+      Self::NEAREST_NEIGHBOR => write!(f, "NEAREST-NEIGHBOR"),
+      Self::QUADRANT => write!(f, "QUADRANT"),
+      Self::POPMUSIC => write!(f, "POPMUSIC"),
+    }
+  }
+}
+
+/// Specifies the algorithm for obtaining an initial tour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+#[non_exhaustive]
+pub enum InitialTourAlgorithm {
+  BORUVKA,
+  GREEDY,
+  MOONEY,
+  NEAREST_NEIGHBOR,
+  QUICK_BORUVKA,
+  SIERPINSKI,
+  /// Random walk (the LKH default)
+  WALK,
+  /// LKH-3 only
+  CVRP,
+  /// LKH-3 only
+  MTSP,
+  /// LKH-3 only
+  SOP,
+}
+
+impl Display for InitialTourAlgorithm {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::NEAREST_NEIGHBOR => write!(f, "NEAREST-NEIGHBOR"),
+      Self::QUICK_BORUVKA => write!(f, "QUICK-BORUVKA"),
+      other => write!(f, "{other:?}"),
+    }
+  }
+}
+
+/// Specifies the objective function type for a multiple traveling salesman problem (LKH-3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+#[non_exhaustive]
+pub enum MtspObjective {
+  /// Minimize the length of the longest route
+  MINMAX,
+  /// Minimize the size of the largest route
+  MINMAX_SIZE,
+  /// Minimize the total length of all routes
+  MINSUM,
+}
+
+/// Specifies the recombination operator used by the genetic algorithm (LKH-3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Recombination {
+  IPT,
+  GPX2,
+}
+
+/// Restricts which cycles may be patched by `PATCHING_A` / `PATCHING_C`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PatchingMode {
+  RESTRICTED,
+  EXTENDED,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Patching(u32, Option<PatchingMode>);
+
+impl Display for Patching {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self.1 {
+      Some(mode) => write!(f, "{} {mode:?}", self.0),
+      None => write!(f, "{}", self.0),
+    }
+  }
+}
+
+/// Specifies how the problem is partitioned into subproblems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+#[non_exhaustive]
+pub enum Partitioning {
+  DELAUNAY,
+  KARP,
+  K_CENTER,
+  K_MEANS,
+  MOORE,
+  ROHE,
+  SIERPINSKI,
+}
+
+impl Display for Partitioning {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::K_CENTER => write!(f, "K-CENTER"),
+      Self::K_MEANS => write!(f, "K-MEANS"),
+      other => write!(f, "{other:?}"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Subproblem {
+  size: u32,
+  partitioning: Option<Partitioning>,
+  borders: bool,
+  compressed: bool,
+}
+
+impl Display for Subproblem {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.size)?;
+    if let Some(p) = self.partitioning { write!(f, " {p}")?; }
+    if self.borders { write!(f, " BORDERS")?; }
+    if self.compressed { write!(f, " COMPRESSED")?; }
+    Ok(())
+  }
+}
+
+/// The `B Q [L]` of a black-and-white TSP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Bwtsp {
+  black: u32,
+  max_whites: u32,
+  max_length: Option<i64>,
+}
+
+impl Display for Bwtsp {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} {}", self.black, self.max_whites)?;
+    if let Some(l) = self.max_length { write!(f, " {l}")?; }
+    Ok(())
+  }
+}
+
+/// Appends `suffix` to a value when `flag` is set, e.g. `MAX_CANDIDATES = 5 SYMMETRIC`.
+struct Flagged<'a>(u32, bool, &'a str);
+
+impl Display for Flagged<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if self.1 { write!(f, "{} {}", self.0, self.2) } else { write!(f, "{}", self.0) }
+  }
+}
+
+struct YesNo(bool);
+
+impl Display for YesNo {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", if self.0 { "YES" } else { "NO" })
+  }
+}
+
+fn invalid(keyword: &'static str, message: impl Display) -> LkhError {
+  LkhError::InvalidParameter { keyword, message: message.to_string() }
+}
+
+fn at_least<T: PartialOrd + Display + Copy>(keyword: &'static str, value: Option<T>, min: T) -> crate::Result<()> {
+  match value {
+    Some(v) if v < min => Err(invalid(keyword, format!("{v} is less than {min}"))),
+    _ => Ok(())
+  }
+}
+
+fn finite(keyword: &'static str, value: Option<f64>) -> crate::Result<()> {
+  match value {
+    Some(v) if !v.is_finite() => Err(invalid(keyword, format!("{v} is not a finite number"))),
+    _ => Ok(())
+  }
+}
+
+fn entry(w: &mut impl Write, keyword: &str, value: Option<impl Display>) -> std::io::Result<()> {
+  match value {
+    Some(v) => writeln!(w, "{keyword} = {v}"),
+    None => Ok(())
+  }
+}
+
+fn path_entry(w: &mut impl Write, keyword: &str, value: Option<&PathBuf>) -> std::io::Result<()> {
+  entry(w, keyword, value.map(|p| p.display()))
+}
+
+impl Parameters {
+  pub fn new() -> Self { Self::default() }
+
+  /// Specifies the name of the problem file.
+  pub fn problem_file(mut self, path: impl AsRef<Path>) -> Self {
+    self.problem_file = Some(path.as_ref().to_owned()); self
+  }
+
+  /// Specifies the name of a file to which the best tour is to be written.
+  pub fn tour_file(mut self, path: impl AsRef<Path>) -> Self {
+    self.tour_file = Some(path.as_ref().to_owned()); self
+  }
+
+  /// Specifies the name of a file to which the best tour is to be written each time a
+  /// new best tour is found.
+  pub fn output_tour_file(mut self, path: impl AsRef<Path>) -> Self {
+    self.output_tour_file = Some(path.as_ref().to_owned()); self
+  }
+
+  /// Specifies the name of a file containing a tour to be used as the initial tour in
+  /// the search.
+  pub fn initial_tour_file(mut self, path: impl AsRef<Path>) -> Self {
+    self.initial_tour_file = Some(path.as_ref().to_owned()); self
+  }
+
+  /// Specifies the name of a file containing a tour. The tour is used to limit the
+  /// search (the last edge to be excluded in a non-gainful move must not belong to it).
+  pub fn input_tour_file(mut self, path: impl AsRef<Path>) -> Self {
+    self.input_tour_file = Some(path.as_ref().to_owned()); self
+  }
+
+  /// Adds a tour file to be merged with the tours found by LKH. May be given repeatedly.
+  pub fn merge_tour_file(mut self, path: impl AsRef<Path>) -> Self {
+    self.merge_tour_files.push(path.as_ref().to_owned()); self
+  }
+
+  /// Adds a file the candidate sets are read from (or written to). May be given repeatedly.
+  pub fn candidate_file(mut self, path: impl AsRef<Path>) -> Self {
+    self.candidate_files.push(path.as_ref().to_owned()); self
+  }
+
+  /// Adds a file of candidate edges in Concorde format. May be given repeatedly.
+  pub fn edge_file(mut self, path: impl AsRef<Path>) -> Self {
+    self.edge_files.push(path.as_ref().to_owned()); self
+  }
+
+  /// Specifies the name of a file the penalties (pi-values) are read from (or written to).
+  pub fn pi_file(mut self, path: impl AsRef<Path>) -> Self {
+    self.pi_file = Some(path.as_ref().to_owned()); self
+  }
+
+  /// Specifies the file a multiple traveling salesman solution is written to (LKH-3).
+  pub fn mtsp_solution_file(mut self, path: impl AsRef<Path>) -> Self {
+    self.mtsp_solution_file = Some(path.as_ref().to_owned()); self
+  }
+
+  /// Specifies the file a solution is written to in SINTEF format (LKH-3).
+  pub fn sintef_solution_file(mut self, path: impl AsRef<Path>) -> Self {
+    self.sintef_solution_file = Some(path.as_ref().to_owned()); self
+  }
+
+  /// Specifies the tour used for dividing the problem into subproblems.
+  pub fn subproblem_tour_file(mut self, path: impl AsRef<Path>) -> Self {
+    self.subproblem_tour_file = Some(path.as_ref().to_owned()); self
+  }
+
+  /// The number of candidate edges to be associated with each node during the ascent.
+  /// Must be at least 2.
+  pub fn ascent_candidates(mut self, n: u32) -> Self { self.ascent_candidates = Some(n); self }
+
+  /// The number of backbone trials in each run.
+  pub fn backbone_trials(mut self, n: u32) -> Self { self.backbone_trials = Some(n); self }
+
+  /// Specifies whether a backtracking K-opt move is to be used as the first move.
+  pub fn backtracking(mut self, on: bool) -> Self { self.backtracking = Some(on); self }
+
+  /// Makes this a black-and-white TSP (LKH-3): the first `black` nodes are black, no path
+  /// between black nodes may visit more than `max_whites` white nodes, nor be longer than
+  /// `max_length` if given.
+  pub fn bwtsp(mut self, black: u32, max_whites: u32, max_length: Option<i64>) -> Self {
+    self.bwtsp = Some(Bwtsp { black, max_whites, max_length }); self
+  }
+
+  /// Specifies the type of candidate sets.
+  pub fn candidate_set_kind(mut self, kind: CandidateSetKind) -> Self {
+    self.candidate_set_kind = Some(kind); self
+  }
+
+  /// Specifies the depot node of a multiple traveling salesman or vehicle routing problem.
+  pub fn depot(mut self, depot: Node) -> Self { self.depot = Some(depot); self }
+
+  /// The maximum alpha-value allowed for any candidate edge is `excess` times the absolute
+  /// value of the lower bound of a solution tour. Must be non-negative.
+  pub fn excess(mut self, excess: f64) -> Self { self.excess = Some(excess); self }
+
+  /// Number of extra candidate edges to be added to each candidate set, optionally
+  /// made symmetric.
+  pub fn extra_candidates(mut self, n: u32, symmetric: bool) -> Self {
+    self.extra_candidates = Some((n, symmetric)); self
+  }
+
+  /// Specifies how extra candidate edges are chosen. Only `NEAREST_NEIGHBOR` and
+  /// `QUADRANT` are accepted by LKH.
+  pub fn extra_candidate_set_kind(mut self, kind: CandidateSetKind) -> Self {
+    self.extra_candidate_set_kind = Some(kind); self
+  }
+
+  /// Specifies whether the Gain23 function is used.
+  pub fn gain23(mut self, on: bool) -> Self { self.gain23 = Some(on); self }
+
+  /// Specifies whether Lin and Kernighan's gain criterion is used.
+  pub fn gain_criterion(mut self, on: bool) -> Self { self.gain_criterion = Some(on); self }
+
+  /// The length of the first period in the ascent.
+  pub fn initial_period(mut self, n: u32) -> Self { self.initial_period = Some(n); self }
+
+  /// The initial step size used in the ascent. Must be at least 1.
+  pub fn initial_step_size(mut self, n: u32) -> Self { self.initial_step_size = Some(n); self }
+
+  /// Specifies the algorithm for obtaining an initial tour.
+  pub fn initial_tour_algorithm(mut self, algorithm: InitialTourAlgorithm) -> Self {
+    self.initial_tour_algorithm = Some(algorithm); self
+  }
+
+  /// The fraction of the initial tour to be constructed from `INITIAL_TOUR_FILE` edges.
+  /// Must be within `[0, 1]`.
+  pub fn initial_tour_fraction(mut self, fraction: f64) -> Self {
+    self.initial_tour_fraction = Some(fraction); self
+  }
+
+  /// The value K of random restarts by K-swap kicks. Must be 0 (segment reversal) or at least 4.
+  pub fn kick_type(mut self, k: u32) -> Self { self.kick_type = Some(k); self }
+
+  /// The number of times to "kick" a tour found by Lin-Kernighan.
+  pub fn kicks(mut self, n: u32) -> Self { self.kicks = Some(n); self }
+
+  /// Specifies whether the makespan (the length of the longest route) is minimized in a
+  /// multiple traveling salesman or vehicle routing problem (LKH-3).
+  pub fn makespan(mut self, on: bool) -> Self { self.makespan = Some(on); self }
+
+  /// The maximum number of candidate edges considered at each level of the search for a move.
+  pub fn max_breadth(mut self, n: u32) -> Self { self.max_breadth = Some(n); self }
+
+  /// The maximum number of candidate edges to be associated with each node, optionally
+  /// made symmetric.
+  pub fn max_candidates(mut self, n: u32, symmetric: bool) -> Self {
+    self.max_candidates = Some((n, symmetric)); self
+  }
+
+  /// The maximum number of swaps (flips) allowed in any search for a tour improvement.
+  pub fn max_swaps(mut self, n: u32) -> Self { self.max_swaps = Some(n); self }
+
+  /// The maximum number of trials in each run.
+  pub fn max_trials(mut self, n: u32) -> Self { self.max_trials = Some(n); self }
+
+  /// Specifies the sequential move type to be used in local search. A value `k` signifies
+  /// that a sequential k-opt move is used. Must be at least 2.
+  pub fn move_type(mut self, k: u32) -> Self { self.move_type = Some((k, false)); self }
+
+  /// As [`Parameters::move_type`], but using LKH-3's special (non-sequential-capable) 3- or 5-opt moves.
+  pub fn special_move_type(mut self, k: u32) -> Self { self.move_type = Some((k, true)); self }
+
+  /// The minimum number of cities each salesman must visit (LKH-3).
+  pub fn mtsp_min_size(mut self, n: u32) -> Self { self.mtsp_min_size = Some(n); self }
+
+  /// The maximum number of cities each salesman may visit (LKH-3).
+  pub fn mtsp_max_size(mut self, n: u32) -> Self { self.mtsp_max_size = Some(n); self }
+
+  /// Specifies the objective function of a multiple traveling salesman problem (LKH-3).
+  pub fn mtsp_objective(mut self, objective: MtspObjective) -> Self {
+    self.mtsp_objective = Some(objective); self
+  }
+
+  /// The non-sequential move type to be used. Must be at least 4.
+  pub fn nonsequential_move_type(mut self, k: u32) -> Self {
+    self.nonsequential_move_type = Some(k); self
+  }
+
+  /// A known optimal tour length. A run is stopped as soon as a tour of this length is found.
+  pub fn optimum(mut self, cost: i64) -> Self { self.optimum = Some(cost); self }
+
+  /// The maximum number of disjoint alternating cycles to be used for patching.
+  pub fn patching_a(mut self, n: u32, mode: Option<PatchingMode>) -> Self {
+    self.patching_a = Some(Patching(n, mode)); self
+  }
+
+  /// The maximum number of disjoint cycles to be patched in an attempt to find a
+  /// feasible and gainful move.
+  pub fn patching_c(mut self, n: u32, mode: Option<PatchingMode>) -> Self {
+    self.patching_c = Some(Patching(n, mode)); self
+  }
+
+  /// Specifies whether the first generated POPMUSIC tour is used as the initial tour.
+  pub fn popmusic_initial_tour(mut self, on: bool) -> Self {
+    self.popmusic_initial_tour = Some(on); self
+  }
+
+  /// The maximum number of nearest neighbors used as candidates in the POPMUSIC 3-opt search.
+  pub fn popmusic_max_neighbors(mut self, n: u32) -> Self {
+    self.popmusic_max_neighbors = Some(n); self
+  }
+
+  /// The size of the POPMUSIC sample.
+  pub fn popmusic_sample_size(mut self, n: u32) -> Self {
+    self.popmusic_sample_size = Some(n); self
+  }
+
+  /// The number of POPMUSIC solutions to generate.
+  pub fn popmusic_solutions(mut self, n: u32) -> Self { self.popmusic_solutions = Some(n); self }
+
+  /// The number of trials used in the POPMUSIC 3-opt search.
+  pub fn popmusic_trials(mut self, n: u32) -> Self { self.popmusic_trials = Some(n); self }
+
+  /// The maximum size of the population in the genetic algorithm.
+  pub fn population_size(mut self, n: u32) -> Self { self.population_size = Some(n); self }
+
+  /// The internal precision in the representation of transformed distances. Must be at least 1.
+  pub fn precision(mut self, n: u32) -> Self { self.precision = Some(n); self }
+
+  /// Specifies the recombination operator of the genetic algorithm (LKH-3).
+  pub fn recombination(mut self, recombination: Recombination) -> Self {
+    self.recombination = Some(recombination); self
+  }
+
+  /// Specifies whether the choice of the first edge to be broken is restricted.
+  pub fn restricted_search(mut self, on: bool) -> Self { self.restricted_search = Some(on); self }
+
+  /// The total number of runs. Must be at least 1.
+  pub fn runs(mut self, n: u32) -> Self { self.runs = Some(n); self }
+
+  /// The number of salesmen/vehicles (LKH-3). Must be at least 1.
+  pub fn salesmen(mut self, n: u32) -> Self { self.salesmen = Some(n); self }
+
+  /// Scale factor applied to distances (LKH-3). Must be at least 1.
+  pub fn scale(mut self, n: u32) -> Self { self.scale = Some(n); self }
+
+  /// The initial seed for random number generation.
+  pub fn seed(mut self, seed: u32) -> Self { self.seed = Some(seed); self }
+
+  /// Shorthand for a set of parameters tuned for hard instances (LKH-3 `SPECIAL`).
+  pub fn special(mut self, on: bool) -> Self { self.special = on; self }
+
+  /// Specifies whether a run is stopped if the tour length becomes equal to `OPTIMUM`.
+  pub fn stop_at_optimum(mut self, on: bool) -> Self { self.stop_at_optimum = Some(on); self }
+
+  /// Specifies whether the pi-values should be determined by subgradient optimization.
+  pub fn subgradient(mut self, on: bool) -> Self { self.subgradient = Some(on); self }
+
+  /// The number of nodes in a division of the original problem into subproblems.
+  /// Must be 0 (no division) or at least 3.
+  pub fn subproblem_size(
+    mut self,
+    size: u32,
+    partitioning: Option<Partitioning>,
+    borders: bool,
+    compressed: bool
+  ) -> Self {
+    self.subproblem_size = Some(Subproblem { size, partitioning, borders, compressed }); self
+  }
+
+  /// The move type to be used for all moves following the first one in a sequence of
+  /// moves. Must be 0 (same as `MOVE_TYPE`) or at least 2.
+  pub fn subsequent_move_type(mut self, k: u32, special: bool) -> Self {
+    self.subsequent_move_type = Some((k, special)); self
+  }
+
+  /// Specifies whether patching is used for moves following the first one.
+  pub fn subsequent_patching(mut self, on: bool) -> Self {
+    self.subsequent_patching = Some(on); self
+  }
+
+  /// A time limit in seconds for each run. Must be non-negative.
+  pub fn time_limit(mut self, seconds: f64) -> Self { self.time_limit = Some(seconds); self }
+
+  /// A total time limit in seconds (LKH-3). Must be non-negative.
+  pub fn total_time_limit(mut self, seconds: f64) -> Self {
+    self.total_time_limit = Some(seconds); self
+  }
+
+  /// The level of detail of the output given during the solution process.
+  pub fn trace_level(mut self, level: u32) -> Self { self.trace_level = Some(level); self }
+
+  /// The number of vehicles of a vehicle routing problem (LKH-3); a synonym of `SALESMEN`.
+  /// Must be at least 1.
+  pub fn vehicles(mut self, n: u32) -> Self { self.vehicles = Some(n); self }
+
+  /// Checks every value against the range LKH accepts for its keyword.
+  pub fn validate(&self) -> crate::Result<()> {
+    at_least("ASCENT_CANDIDATES", self.ascent_candidates, 2)?;
+    at_least("EXCESS", self.excess, 0.)?;
+    at_least("INITIAL_STEP_SIZE", self.initial_step_size, 1)?;
+    at_least("MOVE_TYPE", self.move_type.map(|(k, _)| k), 2)?;
+    at_least("NONSEQUENTIAL_MOVE_TYPE", self.nonsequential_move_type, 4)?;
+    at_least("PRECISION", self.precision, 1)?;
+    at_least("RUNS", self.runs, 1)?;
+    at_least("SALESMEN", self.salesmen, 1)?;
+    at_least("SCALE", self.scale, 1)?;
+    at_least("TIME_LIMIT", self.time_limit, 0.)?;
+    at_least("TOTAL_TIME_LIMIT", self.total_time_limit, 0.)?;
+    at_least("VEHICLES", self.vehicles, 1)?;
+    finite("EXCESS", self.excess)?;
+    finite("INITIAL_TOUR_FRACTION", self.initial_tour_fraction)?;
+    finite("TIME_LIMIT", self.time_limit)?;
+    finite("TOTAL_TIME_LIMIT", self.total_time_limit)?;
+
+    if let Some(f) = self.initial_tour_fraction {
+      if !(0. ..=1.).contains(&f) {
+        return Err(invalid("INITIAL_TOUR_FRACTION", format!("{f} is not within [0, 1]")))
+      }
+    }
+
+    if let Some(k @ 1..=3) = self.kick_type {
+      return Err(invalid("KICK_TYPE", format!("{k} must be 0 or at least 4")))
+    }
+
+    if let Some(Subproblem { size: s @ 1..=2, .. }) = self.subproblem_size {
+      return Err(invalid("SUBPROBLEM_SIZE", format!("{s} must be 0 or at least 3")))
+    }
+
+    if let Some((1, _)) = self.subsequent_move_type {
+      return Err(invalid("SUBSEQUENT_MOVE_TYPE", "1 must be 0 or at least 2"))
+    }
+
+    if let Some(kind) = self.extra_candidate_set_kind {
+      if !matches!(kind, CandidateSetKind::NEAREST_NEIGHBOR | CandidateSetKind::QUADRANT) {
+        return Err(invalid("EXTRA_CANDIDATE_SET_TYPE", format!("{kind} is not NEAREST-NEIGHBOR or QUADRANT")))
+      }
+    }
+
+    if let (Some(min), Some(max)) = (self.mtsp_min_size, self.mtsp_max_size) {
+      if min > max {
+        return Err(invalid("MTSP_MIN_SIZE", format!("{min} exceeds MTSP_MAX_SIZE {max}")))
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Validates the parameters and writes them in LKH parameter file syntax.
+  pub fn write(&self, w: &mut impl Write) -> crate::Result<()> {
+    self.validate()?;
+
+    path_entry(w, "PROBLEM_FILE", self.problem_file.as_ref())?;
+    path_entry(w, "TOUR_FILE", self.tour_file.as_ref())?;
+    path_entry(w, "OUTPUT_TOUR_FILE", self.output_tour_file.as_ref())?;
+    path_entry(w, "INITIAL_TOUR_FILE", self.initial_tour_file.as_ref())?;
+    path_entry(w, "INPUT_TOUR_FILE", self.input_tour_file.as_ref())?;
+    self.merge_tour_files.iter().try_for_each(|p| path_entry(w, "MERGE_TOUR_FILE", Some(p)))?;
+    self.candidate_files.iter().try_for_each(|p| path_entry(w, "CANDIDATE_FILE", Some(p)))?;
+    self.edge_files.iter().try_for_each(|p| path_entry(w, "EDGE_FILE", Some(p)))?;
+    path_entry(w, "PI_FILE", self.pi_file.as_ref())?;
+    path_entry(w, "MTSP_SOLUTION_FILE", self.mtsp_solution_file.as_ref())?;
+    path_entry(w, "SINTEF_SOLUTION_FILE", self.sintef_solution_file.as_ref())?;
+    path_entry(w, "SUBPROBLEM_TOUR_FILE", self.subproblem_tour_file.as_ref())?;
+
+    if self.special { writeln!(w, "SPECIAL")?; }
+
+    entry(w, "ASCENT_CANDIDATES", self.ascent_candidates)?;
+    entry(w, "BACKBONE_TRIALS", self.backbone_trials)?;
+    entry(w, "BACKTRACKING", self.backtracking.map(YesNo))?;
+    entry(w, "BWTSP", self.bwtsp)?;
+    entry(w, "CANDIDATE_SET_TYPE", self.candidate_set_kind)?;
+    entry(w, "DEPOT", self.depot.map(|d| d + 1))?; // LKH is 1-indexed
+    entry(w, "EXCESS", self.excess)?;
+    entry(w, "EXTRA_CANDIDATES", self.extra_candidates.map(|(n, s)| Flagged(n, s, "SYMMETRIC")))?;
+    entry(w, "EXTRA_CANDIDATE_SET_TYPE", self.extra_candidate_set_kind)?;
+    entry(w, "GAIN23", self.gain23.map(YesNo))?;
+    entry(w, "GAIN_CRITERION", self.gain_criterion.map(YesNo))?;
+    entry(w, "INITIAL_PERIOD", self.initial_period)?;
+    entry(w, "INITIAL_STEP_SIZE", self.initial_step_size)?;
+    entry(w, "INITIAL_TOUR_ALGORITHM", self.initial_tour_algorithm)?;
+    entry(w, "INITIAL_TOUR_FRACTION", self.initial_tour_fraction)?;
+    entry(w, "KICK_TYPE", self.kick_type)?;
+    entry(w, "KICKS", self.kicks)?;
+    entry(w, "MAKESPAN", self.makespan.map(YesNo))?;
+    entry(w, "MAX_BREADTH", self.max_breadth)?;
+    entry(w, "MAX_CANDIDATES", self.max_candidates.map(|(n, s)| Flagged(n, s, "SYMMETRIC")))?;
+    entry(w, "MAX_SWAPS", self.max_swaps)?;
+    entry(w, "MAX_TRIALS", self.max_trials)?;
+    entry(w, "MOVE_TYPE", self.move_type.map(|(k, s)| Flagged(k, s, "SPECIAL")))?;
+    entry(w, "MTSP_MIN_SIZE", self.mtsp_min_size)?;
+    entry(w, "MTSP_MAX_SIZE", self.mtsp_max_size)?;
+    entry(w, "MTSP_OBJECTIVE", self.mtsp_objective.map(|o| format!("{o:?}")))?;
+    entry(w, "NONSEQUENTIAL_MOVE_TYPE", self.nonsequential_move_type)?;
+    entry(w, "OPTIMUM", self.optimum)?;
+    entry(w, "PATCHING_A", self.patching_a)?;
+    entry(w, "PATCHING_C", self.patching_c)?;
+    entry(w, "POPMUSIC_INITIAL_TOUR", self.popmusic_initial_tour.map(YesNo))?;
+    entry(w, "POPMUSIC_MAX_NEIGHBORS", self.popmusic_max_neighbors)?;
+    entry(w, "POPMUSIC_SAMPLE_SIZE", self.popmusic_sample_size)?;
+    entry(w, "POPMUSIC_SOLUTIONS", self.popmusic_solutions)?;
+    entry(w, "POPMUSIC_TRIALS", self.popmusic_trials)?;
+    entry(w, "POPULATION_SIZE", self.population_size)?;
+    entry(w, "PRECISION", self.precision)?;
+    entry(w, "RECOMBINATION", self.recombination.map(|r| format!("{r:?}")))?;
+    entry(w, "RESTRICTED_SEARCH", self.restricted_search.map(YesNo))?;
+    entry(w, "RUNS", self.runs)?;
+    entry(w, "SALESMEN", self.salesmen)?;
+    entry(w, "SCALE", self.scale)?;
+    entry(w, "SEED", self.seed)?;
+    entry(w, "STOP_AT_OPTIMUM", self.stop_at_optimum.map(YesNo))?;
+    entry(w, "SUBGRADIENT", self.subgradient.map(YesNo))?;
+    entry(w, "SUBPROBLEM_SIZE", self.subproblem_size)?;
+    entry(w, "SUBSEQUENT_MOVE_TYPE", self.subsequent_move_type.map(|(k, s)| Flagged(k, s, "SPECIAL")))?;
+    entry(w, "SUBSEQUENT_PATCHING", self.subsequent_patching.map(YesNo))?;
+    entry(w, "TIME_LIMIT", self.time_limit)?;
+    entry(w, "TOTAL_TIME_LIMIT", self.total_time_limit)?;
+    entry(w, "TRACE_LEVEL", self.trace_level)?;
+    entry(w, "VEHICLES", self.vehicles)?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn written(p: &Parameters) -> String {
+    let mut m = vec![];
+    p.write(&mut m).unwrap();
+    String::from_utf8(m).unwrap()
+  }
+
+  #[test]
+  fn writes_only_set_keywords() {
+    let p = Parameters::new()
+      .problem_file("a.tsp")
+      .runs(3)
+      .move_type(5)
+      .patching_c(3, Some(PatchingMode::RESTRICTED))
+      .candidate_set_kind(CandidateSetKind::NEAREST_NEIGHBOR)
+      .gain23(false)
+      .depot(0);
+
+    assert_eq!(
+      written(&p),
+      "PROBLEM_FILE = a.tsp\n\
+       CANDIDATE_SET_TYPE = NEAREST-NEIGHBOR\n\
+       DEPOT = 1\n\
+       GAIN23 = NO\n\
+       MOVE_TYPE = 5\n\
+       PATCHING_C = 3 RESTRICTED\n\
+       RUNS = 3\n"
+    );
+  }
+
+  #[test]
+  fn rejects_out_of_range() {
+    assert!(Parameters::new().runs(0).validate().is_err());
+    assert!(Parameters::new().move_type(1).validate().is_err());
+    assert!(Parameters::new().kick_type(3).validate().is_err());
+    assert!(Parameters::new().kick_type(0).validate().is_ok());
+    assert!(Parameters::new().initial_tour_fraction(1.5).validate().is_err());
+    assert!(Parameters::new().mtsp_min_size(4).mtsp_max_size(2).validate().is_err());
+    assert!(Parameters::new().initial_step_size(0).validate().is_err());
+    for nan in [Parameters::new().excess(f64::NAN), Parameters::new().time_limit(f64::NAN), Parameters::new().total_time_limit(f64::INFINITY)] {
+      assert!(matches!(nan.validate(), Err(LkhError::InvalidParameter { .. })));
+    }
+  }
+
+  #[test]
+  fn writes_lkh3_problem_keywords() {
+    let p = Parameters::new().bwtsp(3, 2, Some(50)).makespan(true).vehicles(4).initial_step_size(2);
+    assert_eq!(written(&p), "BWTSP = 3 2 50\nINITIAL_STEP_SIZE = 2\nMAKESPAN = YES\nVEHICLES = 4\n");
+  }
 }
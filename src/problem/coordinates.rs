@@ -1,6 +1,6 @@
 use std::io::Write;
 
-use crate::edges::EdgeData;
+use crate::{edge_weights::EdgeWeights, edges::EdgeData, node_data::NodeData};
 
 /// A `Coordinates` can only be made by a `ProblemFile`.
 pub struct Coordinates<W: Write>(W);
@@ -11,7 +11,7 @@ impl<W: Write> Coordinates<W> {
 
   pub fn begin_node_coordinates(
     mut self,
-  ) -> std::io::Result<Self> { 
+  ) -> crate::Result<Self> { 
     writeln!(self.0, "NODE_COORD_SECTION")?; Ok(self)
   }
   
@@ -19,7 +19,7 @@ impl<W: Write> Coordinates<W> {
     mut self,
     index: usize,
     coordinate: &[f32; N]
-  ) -> std::io::Result<Self> { 
+  ) -> crate::Result<Self> { 
     let w = &mut self.0;
     write!(w, "{} ", index + 1)?; // LKH is 1-indexed (not 0-indexed)
 
@@ -35,7 +35,7 @@ impl<W: Write> Coordinates<W> {
   pub fn write_coordinates<const N: usize>(
     self,
     coordinates: impl Iterator<Item = [f32; N]>
-  ) -> std::io::Result<Self> {
+  ) -> crate::Result<Self> {
     coordinates
       .into_iter()
       .enumerate()
@@ -45,7 +45,11 @@ impl<W: Write> Coordinates<W> {
       .map(|s| s)
   }
 
+  pub fn edge_weights(self) -> EdgeWeights<W> { EdgeWeights::new(self.0) }
+
   pub fn edges(self) -> EdgeData<W> { EdgeData::new(self.0) }
 
+  pub fn node_data(self) -> NodeData<W> { NodeData::new(self.0) }
+
   pub fn finish(self) -> W { self.0 }
 }
\ No newline at end of file
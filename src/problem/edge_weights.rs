@@ -0,0 +1,135 @@
+use std::io::Write;
+use crate::{edges::EdgeData, fixed_edges::FixedEdges, header::EdgeWeightFormat, matrix::Matrix, node_data::NodeData, LkhError};
+
+/// The `EDGE_WEIGHT_SECTION`, for problems with `EDGE_WEIGHT_TYPE: EXPLICIT`.
+pub struct EdgeWeights<W: Write>(W);
+
+impl<W: Write> EdgeWeights<W> {
+  #[inline(always)] #[must_use]
+  pub(crate) const fn new(w: W) -> Self { Self(w) }
+
+  pub fn begin_edge_weights(
+    mut self,
+  ) -> crate::Result<Self> {
+    writeln!(self.0, "EDGE_WEIGHT_SECTION")?; Ok(self)
+  }
+
+  /// Writes the dimension on a line of its own, which `TYPE: SOP` problems expect before
+  /// their weights.
+  pub fn write_dimension(
+    mut self,
+    dimension: usize
+  ) -> crate::Result<Self> {
+    writeln!(self.0, "{}", dimension)?; Ok(self)
+  }
+
+  /// Streams the weight of every entry `format` lists, asking `weight(i, j)` for each
+  /// (0-indexed). For the triangular formats only one of `(i, j)` and `(j, i)` is asked.
+  pub fn write_with(
+    mut self,
+    format: EdgeWeightFormat,
+    dimension: usize,
+    mut weight: impl FnMut(usize, usize) -> i64
+  ) -> crate::Result<Self> {
+    if format == EdgeWeightFormat::FUNCTION {
+      return Err(LkhError::invalid_problem("FUNCTION edge weights have no EDGE_WEIGHT_SECTION"))
+    }
+
+    // one line per row (or column) of the format, which keeps the file readable
+    let mut line = None;
+    for (i, j) in format.entries(dimension) {
+      let outer = if is_column_wise(format) { j } else { i };
+      match line {
+        Some(l) if l == outer => write!(self.0, " ")?,
+        Some(_) => writeln!(self.0)?,
+        None => {}
+      }
+      line = Some(outer);
+      write!(self.0, "{}", weight(i, j))?;
+    }
+    if line.is_some() { writeln!(self.0)?; }
+
+    Ok(self)
+  }
+
+  /// Writes `matrix` in `format`. Triangular formats require a symmetric matrix.
+  pub fn write_matrix(
+    self,
+    format: EdgeWeightFormat,
+    matrix: &Matrix
+  ) -> crate::Result<Self> {
+    if format.is_triangular() && !matrix.is_symmetric() {
+      return Err(LkhError::invalid_problem(format!("{format:?} requires a symmetric matrix")))
+    }
+    self.write_with(format, matrix.dimension(), |i, j| matrix.get(i, j))
+  }
+
+  /// Writes `weights`, which are already in the order `format` lists them (e.g. the
+  /// rows of the upper triangle for `UPPER_ROW`).
+  pub fn write_triangle(
+    self,
+    format: EdgeWeightFormat,
+    dimension: usize,
+    weights: &[i64]
+  ) -> crate::Result<Self> {
+    let expected = format.entry_count(dimension);
+    if weights.len() != expected {
+      return Err(LkhError::invalid_problem(format!(
+        "{} weights given, {format:?} of dimension {dimension} needs {expected}", weights.len()
+      )))
+    }
+    let mut weights = weights.iter().copied();
+    self.write_with(format, dimension, |_, _| weights.next().unwrap_or_default())
+  }
+
+  pub fn edges(self) -> EdgeData<W> { EdgeData::new(self.0) }
+
+  pub fn node_data(self) -> NodeData<W> { NodeData::new(self.0) }
+
+  pub fn fixed_edges(self) -> FixedEdges<W> { FixedEdges::new(self.0) }
+
+  pub fn finish(self) -> W { self.0 }
+}
+
+fn is_column_wise(format: EdgeWeightFormat) -> bool {
+  matches!(
+    format,
+    EdgeWeightFormat::UPPER_COL | EdgeWeightFormat::LOWER_COL | EdgeWeightFormat::UPPER_DIAG_COL | EdgeWeightFormat::LOWER_DIAG_COL
+  )
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{instance::ProblemInstance, Problem, header::{EdgeWeightKind, ProblemKind}};
+  use super::*;
+
+  const FORMATS: [EdgeWeightFormat; 9] = [
+    EdgeWeightFormat::FULL_MATRIX, EdgeWeightFormat::UPPER_ROW, EdgeWeightFormat::LOWER_ROW,
+    EdgeWeightFormat::UPPER_DIAG_ROW, EdgeWeightFormat::LOWER_DIAG_ROW, EdgeWeightFormat::UPPER_COL,
+    EdgeWeightFormat::LOWER_COL, EdgeWeightFormat::UPPER_DIAG_COL, EdgeWeightFormat::LOWER_DIAG_COL,
+  ];
+
+  #[test]
+  fn writes_every_format() {
+    let full = Matrix::from_fn(4, |i, j| if i == j { 0 } else { (i + j) as i64 * 7 });
+
+    for format in FORMATS {
+      let p = ProblemInstance::new(ProblemKind::TSP, 4).edge_weights(format, full.clone());
+      let read: ProblemInstance = String::from_utf8(p.to_tsplib().unwrap()).unwrap().parse().unwrap();
+      assert_eq!(read.edge_weights.as_ref(), Some(&full), "{format:?}");
+      assert_eq!(read.edge_weight_kind, Some(EdgeWeightKind::EXPLICIT));
+    }
+  }
+
+  #[test]
+  fn writes_triangles_in_order() {
+    let w = Problem::with(vec![]).edge_weights()
+      .write_triangle(EdgeWeightFormat::UPPER_ROW, 3, &[1, 2, 3]).unwrap()
+      .finish();
+    assert_eq!(String::from_utf8(w).unwrap(), "1 2\n3\n");
+
+    assert!(Problem::with(vec![]).edge_weights().write_triangle(EdgeWeightFormat::UPPER_ROW, 3, &[1, 2]).is_err());
+    let asymmetric = Matrix::from_rows(&[[0, 1], [2, 0]]).unwrap();
+    assert!(Problem::with(vec![]).edge_weights().write_matrix(EdgeWeightFormat::LOWER_ROW, &asymmetric).is_err());
+  }
+}
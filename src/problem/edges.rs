@@ -1,5 +1,5 @@
 use std::io::Write;
-use crate::{fixed_edges::FixedEdges, util, Edge, Node};
+use crate::{fixed_edges::FixedEdges, node_data::NodeData, util, Edge, Node};
 
 pub struct EdgeData<W: Write>(W);
 
@@ -9,26 +9,26 @@ impl<W: Write> EdgeData<W> {
 
   pub fn begin_adjacency(
     mut self,
-  ) -> std::io::Result<Self> {
-    writeln!(self.0, "EDGE_DATA_SECTION").map(|_| self)
+  ) -> crate::Result<Self> {
+    writeln!(self.0, "EDGE_DATA_SECTION")?; Ok(self)
   }
   
   pub fn begin_adjacency_edge<'a>(
     mut self,
     source: Node
-  ) -> std::io::Result<Self> {
-    write!(self.0, "{} ", source + 1).map(|_| self)
+  ) -> crate::Result<Self> {
+    write!(self.0, "{} ", source + 1)?; Ok(self)
   }
   
   pub fn write_adjacency_entry(
     mut self,
     destination: Node
-  ) -> std::io::Result<Self> {
-    write!(self.0, "{} ", destination + 1).map(|_| self)
+  ) -> crate::Result<Self> {
+    write!(self.0, "{} ", destination + 1)?; Ok(self)
   }
   
-  fn end_adjacency_edge(mut self) -> std::io::Result<Self> {
-    util::end_seq(&mut self.0).map(|_| self)
+  fn end_adjacency_edge(mut self) -> crate::Result<Self> {
+    util::end_seq(&mut self.0)?; Ok(self)
   }
   
   /// the `node_edges` iterator needs to yield repeats of the same
@@ -36,7 +36,7 @@ impl<W: Write> EdgeData<W> {
   pub fn write_adjacency_edges<'a>(
     mut self,
     mut node_edges: impl Iterator<Item = Edge>
-  ) -> std::io::Result<Self> {
+  ) -> crate::Result<Self> {
   
     let mut current = None;
 
@@ -62,7 +62,36 @@ impl<W: Write> EdgeData<W> {
     self.end_adjacency_edge()
   }
 
+  /// Writes `edges` in `EDGE_LIST` format, one edge per line.
+  pub fn write_edge_list(
+    mut self,
+    edges: impl Iterator<Item = Edge>
+  ) -> crate::Result<Self> {
+    for [a, b] in edges {
+      writeln!(self.0, "{} {}", a + 1, b + 1)?;
+    }
+    Ok(self)
+  }
+
+  /// Writes `edges` in `EDGE_LIST` format with the weight of each edge after its nodes.
+  pub fn write_weighted_edge_list(
+    mut self,
+    edges: impl Iterator<Item = (Edge, i64)>
+  ) -> crate::Result<Self> {
+    for ([a, b], w) in edges {
+      writeln!(self.0, "{} {} {}", a + 1, b + 1, w)?;
+    }
+    Ok(self)
+  }
+
+  /// Closes the `EDGE_DATA_SECTION`.
+  pub fn end_edge_data(mut self) -> crate::Result<Self> {
+    util::end_seq(&mut self.0)?; Ok(self)
+  }
+
   pub fn fixed_edges(self) -> FixedEdges<W> { FixedEdges::new(self.0) }
 
+  pub fn node_data(self) -> NodeData<W> { NodeData::new(self.0) }
+
   pub fn finish(self) -> W { self.0 }
 }
@@ -1,5 +1,5 @@
 use std::io::Write;
-use crate::Edge;
+use crate::{node_data::NodeData, util, Edge, Node};
 
 pub struct FixedEdges<W: Write>(W);
 
@@ -10,14 +10,16 @@ impl<W: Write> FixedEdges<W> {
   /// edges we demand appear in the solution
   pub fn begin_fixed_edges(
     mut self
-  ) -> std::io::Result<Self> {
-    writeln!(&mut self.0, "FIXED_EDGES_SECTION").map(|_| self)
+  ) -> crate::Result<Self> {
+    writeln!(&mut self.0, "FIXED_EDGES_SECTION")?; Ok(self)
   }
 
+  /// Writes the nodes of `edges` as given, so they must already be 1-indexed as LKH reads
+  /// them. See [`FixedEdges::write_fixed_node_pairs`] for 0-indexed [`Node`]s.
   pub fn write_fixed_edges(
     mut self,
     edges: impl Iterator<Item = Edge>
-  ) -> std::io::Result<Self> {
+  ) -> crate::Result<Self> {
     edges
     .into_iter()
     .try_for_each(|c| {
@@ -26,9 +28,26 @@ impl<W: Write> FixedEdges<W> {
         .try_for_each(|v| write!(&mut self.0, "{} ", v))?;
   
       writeln!(&mut self.0)
-    })
-    .map(|_| self)
+    })?;
+    Ok(self)
   }
 
+  /// Writes `edges` between 0-indexed nodes, one edge per line.
+  pub fn write_fixed_node_pairs(
+    mut self,
+    edges: impl Iterator<Item = [Node; 2]>
+  ) -> crate::Result<Self> {
+    for [a, b] in edges {
+      writeln!(self.0, "{} {}", a + 1, b + 1)?; // LKH is 1-indexed
+    }
+    Ok(self)
+  }
+
+  pub fn end_fixed_edges(mut self) -> crate::Result<Self> {
+    util::end_seq(&mut self.0)?; Ok(self)
+  }
+
+  pub fn node_data(self) -> NodeData<W> { NodeData::new(self.0) }
+
   pub fn finish(self) -> W { self.0 }
 }
@@ -1,7 +1,7 @@
-use std::io::Write;
-use crate::coordinates::Coordinates;
+use std::{io::Write, str::FromStr};
+use crate::{coordinates::Coordinates, node_data::NodeData, LkhError};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum ProblemKind {
   /// Data for a symmetric traveling salesman problem
@@ -14,6 +14,36 @@ pub enum ProblemKind {
   HCP,
   /// Capacitated vehicle routing problem data
   CVRP,
+  /// Capacitated vehicle routing problem with time windows (LKH-3)
+  CVRPTW,
+  /// Open vehicle routing problem, whose vehicles do not return to the depot (LKH-3)
+  OVRP,
+  /// Distance-constrained capacitated vehicle routing problem (LKH-3)
+  DCVRP,
+  /// Vehicle routing problem with backhauls (LKH-3)
+  VRPB,
+  /// Vehicle routing problem with simultaneous pickup and delivery (LKH-3)
+  VRPSPD,
+  /// Traveling salesman problem with time windows (LKH-3)
+  TSPTW,
+  /// Generalized (symmetric) traveling salesman problem (LKH-3)
+  GTSP,
+  /// Generalized asymmetric traveling salesman problem (LKH-3)
+  AGTSP,
+  /// Clustered traveling salesman problem (LKH-3)
+  CTSP,
+  /// Pickup-and-delivery traveling salesman problem (LKH-3)
+  PDTSP,
+  /// Pickup-and-delivery problem with time windows (LKH-3)
+  PDPTW,
+  /// Orienteering problem (LKH-3)
+  OP,
+  /// Traveling repairman problem (LKH-3)
+  TRP,
+  /// Minimum latency problem (LKH-3)
+  MLP,
+  /// Steiner traveling salesman problem, visiting required nodes of a sparse graph (LKH-3)
+  STTSP,
   /// A collection of tours
   TOUR,
 }
@@ -28,7 +58,7 @@ impl<W: Write> Header<W> {
   pub fn name(
     mut self,
     name: &str
-  ) -> std::io::Result<Self> {
+  ) -> crate::Result<Self> {
     writeln!(&mut self.0, "NAME: {}", name)?;
     Ok(self)
   }
@@ -37,7 +67,7 @@ impl<W: Write> Header<W> {
   pub fn problem_kind(
     mut self,
     kind: ProblemKind
-  ) -> std::io::Result<Self> {
+  ) -> crate::Result<Self> {
     writeln!(&mut self.0, "TYPE: {:?}", kind)?;
     Ok(self)
   }
@@ -46,7 +76,7 @@ impl<W: Write> Header<W> {
   pub fn comment(
     mut self,
     comment: &str
-  ) -> std::io::Result<Self> {
+  ) -> crate::Result<Self> {
     writeln!(&mut self.0, "COMMENT: {}", comment)?;
     Ok(self)
   }
@@ -57,7 +87,7 @@ impl<W: Write> Header<W> {
   pub fn dimension(
     mut self,
     dimension: u32
-  ) -> std::io::Result<Self> {
+  ) -> crate::Result<Self> {
     writeln!(&mut self.0, "DIMENSION: {}", dimension)?;
     Ok(self)
   }
@@ -66,7 +96,7 @@ impl<W: Write> Header<W> {
   pub fn capacity(
     mut self,
     capacity: u32
-  ) -> std::io::Result<Self> {
+  ) -> crate::Result<Self> {
     writeln!(&mut self.0, "CAPACITY: {}", capacity)?;
     Ok(self)
   }
@@ -76,7 +106,7 @@ impl<W: Write> Header<W> {
   pub fn edge_weight_kind(
     mut self,
     kind: EdgeWeightKind
-  ) -> std::io::Result<Self> {
+  ) -> crate::Result<Self> {
     writeln!(&mut self.0, "EDGE_WEIGHT_TYPE: {:?}", kind)?;
     Ok(self)
   }
@@ -84,7 +114,7 @@ impl<W: Write> Header<W> {
   pub fn edge_weight_format(
     mut self,
     format: EdgeWeightFormat
-  ) -> std::io::Result<Self> {
+  ) -> crate::Result<Self> {
     writeln!(&mut self.0, "EDGE_WEIGHT_FORMAT: {:?}", format)?;
     Ok(self)
   }
@@ -92,7 +122,7 @@ impl<W: Write> Header<W> {
   pub fn edge_data_format(
     mut self,
     format: EdgeFormat
-  ) -> std::io::Result<Self> {
+  ) -> crate::Result<Self> {
     writeln!(&mut self.0, "EDGE_DATA_FORMAT: {:?}", format)?;
     Ok(self)
   }
@@ -100,7 +130,7 @@ impl<W: Write> Header<W> {
   pub fn node_coord_kind(
     mut self,
     kind: CoordinateKind
-  ) -> std::io::Result<Self> {
+  ) -> crate::Result<Self> {
     writeln!(&mut self.0, "NODE_COORD_TYPE: {:?}", kind)?;
     Ok(self)
   }
@@ -108,21 +138,33 @@ impl<W: Write> Header<W> {
   pub fn display_data_kind(
     mut self,
     kind: DisplayDataKind
-  ) -> std::io::Result<Self> {
+  ) -> crate::Result<Self> {
     writeln!(&mut self.0, "DISPLAY_DATA_TYPE: {:?}", kind)?;
     Ok(self)
   }
 
+  /// Writes a specification keyword this builder has no dedicated method for.
+  pub fn keyword(
+    mut self,
+    keyword: &str,
+    value: impl std::fmt::Display
+  ) -> crate::Result<Self> {
+    writeln!(&mut self.0, "{}: {}", keyword, value)?;
+    Ok(self)
+  }
+
   pub fn coords(
     self,
   ) -> Coordinates<W> {
     Coordinates::new(self.0)
   }
 
+  pub fn node_data(self) -> NodeData<W> { NodeData::new(self.0) }
+
   pub fn finish(self) -> W { self.0 }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 #[non_exhaustive]
 /// Specifies how the edge weights (or distances) are given. The values are
@@ -169,7 +211,7 @@ pub enum EdgeWeightKind {
   SPECIAL,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 #[non_exhaustive]
 pub enum EdgeWeightFormat {
@@ -195,7 +237,7 @@ pub enum EdgeWeightFormat {
   LOWER_DIAG_COL,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 #[non_exhaustive]
 pub enum EdgeFormat {
@@ -209,7 +251,7 @@ impl Default for EdgeFormat {
   fn default() -> Self { Self::ADJ_LIST }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 #[non_exhaustive]
 pub enum CoordinateKind {
@@ -225,7 +267,7 @@ impl Default for CoordinateKind {
   fn default() -> Self { Self::NO_COORDS }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 #[non_exhaustive]
 pub enum DisplayDataKind {
@@ -240,3 +282,78 @@ pub enum DisplayDataKind {
 impl Default for DisplayDataKind {
   fn default() -> Self { Self::COORD_DISPLAY }
 }
+
+/// Parses TSPLIB keyword values, e.g. `"EUC_2D".parse::<EdgeWeightKind>()`.
+macro_rules! keyword_from_str {
+  ($t:ident { $($v:ident),* $(,)? }) => {
+    impl FromStr for $t {
+      type Err = LkhError;
+
+      fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+          $(stringify!($v) => Ok(Self::$v),)*
+          other => Err(LkhError::invalid_problem(format!("unknown {} {other:?}", stringify!($t))))
+        }
+      }
+    }
+  };
+}
+
+keyword_from_str!(ProblemKind { TSP, ATSP, SOP, HCP, CVRP, CVRPTW, OVRP, DCVRP, VRPB, VRPSPD, TSPTW, GTSP, AGTSP, CTSP, PDTSP, PDPTW, OP, TRP, MLP, STTSP, TOUR });
+keyword_from_str!(EdgeWeightKind {
+  EXPLICIT, EUC_2D, EUC_3D, MAX_2D, MAX_3D, MAN_2D, MAN_3D, CEIL, GEO, ATT, XRAY1, XRAY2, SPECIAL
+});
+keyword_from_str!(EdgeWeightFormat {
+  FUNCTION, FULL_MATRIX, UPPER_ROW, LOWER_ROW, UPPER_DIAG_ROW, LOWER_DIAG_ROW,
+  UPPER_COL, LOWER_COL, UPPER_DIAG_COL, LOWER_DIAG_COL
+});
+keyword_from_str!(EdgeFormat { EDGE_LIST, ADJ_LIST });
+keyword_from_str!(CoordinateKind { TWOD_COORDS, THREED_COORDS, NO_COORDS });
+keyword_from_str!(DisplayDataKind { COORD_DISPLAY, TWOD_DISPLAY, NO_DISPLAY });
+
+impl EdgeWeightKind {
+  /// The number of coordinates per node this distance function works on, if it uses coordinates.
+  pub fn coordinate_dimension(&self) -> Option<usize> {
+    match self {
+      Self::EXPLICIT | Self::SPECIAL => None,
+      Self::EUC_3D | Self::MAX_3D | Self::MAN_3D | Self::XRAY1 | Self::XRAY2 => Some(3),
+      _ => Some(2),
+    }
+  }
+}
+
+impl EdgeWeightFormat {
+  /// The `(row, column)` matrix entries of an `EDGE_WEIGHT_SECTION` in this format, in the
+  /// order they appear in the section. `FUNCTION` has no entries.
+  pub fn entries(&self, dimension: usize) -> Box<dyn Iterator<Item = (usize, usize)>> {
+    let n = dimension;
+    match self {
+      Self::FUNCTION => Box::new(std::iter::empty()),
+      Self::FULL_MATRIX => Box::new((0..n).flat_map(move |i| (0..n).map(move |j| (i, j)))),
+      Self::UPPER_ROW => Box::new((0..n).flat_map(move |i| (i + 1..n).map(move |j| (i, j)))),
+      Self::LOWER_ROW => Box::new((0..n).flat_map(|i| (0..i).map(move |j| (i, j)))),
+      Self::UPPER_DIAG_ROW => Box::new((0..n).flat_map(move |i| (i..n).map(move |j| (i, j)))),
+      Self::LOWER_DIAG_ROW => Box::new((0..n).flat_map(|i| (0..=i).map(move |j| (i, j)))),
+      Self::UPPER_COL => Box::new((0..n).flat_map(|j| (0..j).map(move |i| (i, j)))),
+      Self::LOWER_COL => Box::new((0..n).flat_map(move |j| (j + 1..n).map(move |i| (i, j)))),
+      Self::UPPER_DIAG_COL => Box::new((0..n).flat_map(|j| (0..=j).map(move |i| (i, j)))),
+      Self::LOWER_DIAG_COL => Box::new((0..n).flat_map(move |j| (j..n).map(move |i| (i, j)))),
+    }
+  }
+
+  /// The number of entries in an `EDGE_WEIGHT_SECTION` of this format.
+  pub fn entry_count(&self, dimension: usize) -> usize {
+    let n = dimension;
+    match self {
+      Self::FUNCTION => 0,
+      Self::FULL_MATRIX => n * n,
+      Self::UPPER_ROW | Self::LOWER_ROW | Self::UPPER_COL | Self::LOWER_COL => n * n.saturating_sub(1) / 2,
+      _ => n * (n + 1) / 2,
+    }
+  }
+
+  /// Whether the format only describes one triangle of a symmetric matrix.
+  pub fn is_triangular(&self) -> bool {
+    !matches!(self, Self::FUNCTION | Self::FULL_MATRIX)
+  }
+}
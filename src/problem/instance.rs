@@ -0,0 +1,279 @@
+use std::io::Write;
+
+use crate::{header::{CoordinateKind, DisplayDataKind, EdgeFormat, EdgeWeightFormat, EdgeWeightKind, ProblemKind}, matrix::Matrix, Edge, LkhError, Node, Problem};
+
+/// Node coordinates of either dimensionality, indexed by node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeCoordinates {
+  TwoD(Vec<[f32; 2]>),
+  ThreeD(Vec<[f32; 3]>),
+}
+
+impl NodeCoordinates {
+  pub fn len(&self) -> usize {
+    match self {
+      Self::TwoD(c) => c.len(),
+      Self::ThreeD(c) => c.len(),
+    }
+  }
+
+  pub fn is_empty(&self) -> bool { self.len() == 0 }
+}
+
+/// A TSPLIB problem held in memory. All node indices are 0-indexed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProblemInstance {
+  pub name: Option<String>,
+  pub kind: Option<ProblemKind>,
+  pub comments: Vec<String>,
+  pub dimension: u32,
+  pub capacity: Option<u32>,
+  pub edge_weight_kind: Option<EdgeWeightKind>,
+  pub edge_weight_format: Option<EdgeWeightFormat>,
+  pub edge_data_format: Option<EdgeFormat>,
+  pub node_coord_kind: Option<CoordinateKind>,
+  pub display_data_kind: Option<DisplayDataKind>,
+  /// Specification keywords this crate does not model, as `(keyword, value)`
+  pub other_keywords: Vec<(String, String)>,
+
+  pub coordinates: Option<NodeCoordinates>,
+  /// The explicit edge weights, expanded to a full matrix whatever their `EDGE_WEIGHT_FORMAT`
+  pub edge_weights: Option<Matrix>,
+  /// `EDGE_DATA_SECTION`, as a list of edges whatever its `EDGE_DATA_FORMAT`
+  pub edges: Vec<Edge>,
+  /// The weight of every edge in `edges`, for LKH-3 weighted edge lists; empty otherwise
+  pub edge_data_weights: Vec<i64>,
+  pub fixed_edges: Vec<Edge>,
+  pub display_coordinates: Vec<[f32; 2]>,
+  /// Demand per node; empty when there is no `DEMAND_SECTION`
+  pub demands: Vec<i64>,
+  pub depots: Vec<Node>,
+  /// Sections this crate does not model (e.g. LKH-3's `TIME_WINDOW_SECTION`), as
+  /// `(keyword, lines)`, written back verbatim
+  pub other_sections: Vec<(String, Vec<String>)>,
+}
+
+impl ProblemInstance {
+  pub fn new(kind: ProblemKind, dimension: u32) -> Self {
+    Self { kind: Some(kind), dimension, ..Self::default() }
+  }
+
+  pub fn name(mut self, name: impl Into<String>) -> Self { self.name = Some(name.into()); self }
+
+  pub fn comment(mut self, comment: impl Into<String>) -> Self { self.comments.push(comment.into()); self }
+
+  pub fn capacity(mut self, capacity: u32) -> Self { self.capacity = Some(capacity); self }
+
+  /// Adds a specification keyword this model has no field for (e.g. an LKH-3 extension).
+  pub fn keyword(mut self, keyword: impl Into<String>, value: impl ToString) -> Self {
+    self.other_keywords.push((keyword.into(), value.to_string())); self
+  }
+
+  /// Nodes at 2-D coordinates, with weights computed by `kind`.
+  pub fn coordinates_2d(mut self, kind: EdgeWeightKind, coordinates: Vec<[f32; 2]>) -> Self {
+    self.edge_weight_kind = Some(kind);
+    self.coordinates = Some(NodeCoordinates::TwoD(coordinates));
+    self
+  }
+
+  /// Nodes at 3-D coordinates, with weights computed by `kind`.
+  pub fn coordinates_3d(mut self, kind: EdgeWeightKind, coordinates: Vec<[f32; 3]>) -> Self {
+    self.edge_weight_kind = Some(kind);
+    self.node_coord_kind = Some(CoordinateKind::THREED_COORDS);
+    self.coordinates = Some(NodeCoordinates::ThreeD(coordinates));
+    self
+  }
+
+  /// Explicit edge weights, written in `format`.
+  pub fn edge_weights(mut self, format: EdgeWeightFormat, weights: Matrix) -> Self {
+    self.edge_weight_kind = Some(EdgeWeightKind::EXPLICIT);
+    self.edge_weight_format = Some(format);
+    self.edge_weights = Some(weights);
+    self
+  }
+
+  /// The graph's edges, written in `format`.
+  pub fn edges(mut self, format: EdgeFormat, edges: Vec<Edge>) -> Self {
+    self.edge_data_format = Some(format);
+    self.edges = edges;
+    self
+  }
+
+  /// The graph's edges with a weight each, written as an LKH-3 weighted `EDGE_LIST`.
+  pub fn weighted_edges(mut self, edges: Vec<(Edge, i64)>) -> Self {
+    self.edge_data_format = Some(EdgeFormat::EDGE_LIST);
+    (self.edges, self.edge_data_weights) = edges.into_iter().unzip();
+    self
+  }
+
+  pub fn fixed_edge(mut self, edge: Edge) -> Self { self.fixed_edges.push(edge); self }
+
+  pub fn display_coordinates(mut self, coordinates: Vec<[f32; 2]>) -> Self {
+    self.display_data_kind = Some(DisplayDataKind::TWOD_DISPLAY);
+    self.display_coordinates = coordinates;
+    self
+  }
+
+  pub fn demands(mut self, demands: Vec<i64>) -> Self { self.demands = demands; self }
+
+  pub fn depot(mut self, depot: Node) -> Self { self.depots.push(depot); self }
+
+  /// Adds a section this model has no field for, as its lines (including any closing `-1`).
+  pub fn section(mut self, keyword: impl Into<String>, lines: Vec<String>) -> Self {
+    self.other_sections.push((keyword.into(), lines)); self
+  }
+
+  /// The lines of the section `keyword` this model has no field for, if present.
+  pub fn other_section(&self, keyword: &str) -> Option<&[String]> {
+    self.other_sections.iter().find(|(k, _)| k == keyword).map(|(_, lines)| &lines[..])
+  }
+
+  /// Checks that every section agrees with `DIMENSION` and with the header.
+  pub fn validate(&self) -> crate::Result<()> {
+    let n = self.dimension as usize;
+    let fail = |m: String| Err(LkhError::invalid_problem(m));
+
+    if n == 0 { return fail("DIMENSION must be positive".into()) }
+
+    match (&self.coordinates, self.edge_weight_kind.and_then(|k| k.coordinate_dimension())) {
+      (None, Some(_)) => return fail(format!("{:?} requires node coordinates", self.edge_weight_kind.unwrap())),
+      (Some(c), _) if c.len() != n => return fail(format!("{} node coordinates for DIMENSION {n}", c.len())),
+      (Some(NodeCoordinates::TwoD(_)), Some(3)) | (Some(NodeCoordinates::ThreeD(_)), Some(2)) =>
+        return fail(format!("coordinates do not match {:?}", self.edge_weight_kind.unwrap())),
+      _ => {}
+    }
+
+    match (&self.edge_weights, self.edge_weight_kind) {
+      (None, Some(EdgeWeightKind::EXPLICIT)) if self.edge_data_weights.is_empty() => return fail("EXPLICIT requires edge weights".into()),
+      (Some(m), _) if m.dimension() != n => return fail(format!("{0}x{0} edge weights for DIMENSION {n}", m.dimension())),
+      (Some(m), _) if self.edge_weight_format.is_some_and(|f| f.is_triangular()) && !m.is_symmetric() =>
+        return fail(format!("{:?} requires symmetric edge weights", self.edge_weight_format.unwrap())),
+      _ => {}
+    }
+
+    if !self.edge_data_weights.is_empty() && self.edge_data_weights.len() != self.edges.len() {
+      return fail(format!("{} edge weights for {} edges", self.edge_data_weights.len(), self.edges.len()))
+    }
+    if !self.edge_data_weights.is_empty() && self.edge_data_format == Some(EdgeFormat::ADJ_LIST) {
+      return fail("weighted edges must be written as an EDGE_LIST".into())
+    }
+    if let Some([a, b]) = self.edges.iter().chain(&self.fixed_edges).find(|e| e.iter().any(|v| *v as usize >= n)) {
+      return fail(format!("edge [{a}, {b}] is outside DIMENSION {n}"))
+    }
+    if !self.display_coordinates.is_empty() && self.display_coordinates.len() != n {
+      return fail(format!("{} display coordinates for DIMENSION {n}", self.display_coordinates.len()))
+    }
+    if !self.demands.is_empty() && self.demands.len() != n {
+      return fail(format!("{} demands for DIMENSION {n}", self.demands.len()))
+    }
+    if let Some(d) = self.depots.iter().find(|d| **d as usize >= n) {
+      return fail(format!("depot {d} is outside DIMENSION {n}"))
+    }
+
+    Ok(())
+  }
+
+  /// Validates the problem and writes it in TSPLIB format using the section writers.
+  pub fn write_tsplib(&self, w: &mut impl Write) -> crate::Result<()> {
+    self.validate()?;
+
+    let mut h = Problem::with(&mut *w).header();
+    if let Some(name) = &self.name { h = h.name(name)?; }
+    if let Some(kind) = self.kind { h = h.problem_kind(kind)?; }
+    for c in &self.comments { h = h.comment(c)?; }
+    h = h.dimension(self.dimension)?;
+    if let Some(c) = self.capacity { h = h.capacity(c)?; }
+    if let Some(k) = self.edge_weight_kind { h = h.edge_weight_kind(k)?; }
+    if let Some(f) = self.edge_weight_format { h = h.edge_weight_format(f)?; }
+    if let Some(f) = self.edge_data_format { h = h.edge_data_format(f)?; }
+    if let Some(k) = self.node_coord_kind { h = h.node_coord_kind(k)?; }
+    if let Some(k) = self.display_data_kind { h = h.display_data_kind(k)?; }
+    for (k, v) in &self.other_keywords { h = h.keyword(k, v)?; }
+
+    let mut c = h.coords();
+    c = match &self.coordinates {
+      Some(NodeCoordinates::TwoD(v)) => c.begin_node_coordinates()?.write_coordinates(v.iter().copied())?,
+      Some(NodeCoordinates::ThreeD(v)) => c.begin_node_coordinates()?.write_coordinates(v.iter().copied())?,
+      None => c,
+    };
+
+    let mut e = match &self.edge_weights {
+      Some(m) => c.edge_weights()
+        .begin_edge_weights()?
+        .write_matrix(self.edge_weight_format.unwrap_or(EdgeWeightFormat::FULL_MATRIX), m)?
+        .edges(),
+      None => c.edges(),
+    };
+    if !self.edges.is_empty() {
+      e = match self.edge_data_format.unwrap_or_default() {
+        EdgeFormat::EDGE_LIST if !self.edge_data_weights.is_empty() => e.begin_adjacency()?
+          .write_weighted_edge_list(self.edges.iter().copied().zip(self.edge_data_weights.iter().copied()))?,
+        EdgeFormat::EDGE_LIST => e.begin_adjacency()?.write_edge_list(self.edges.iter().copied())?,
+        EdgeFormat::ADJ_LIST => {
+          let mut sorted = self.edges.clone();
+          sorted.sort();
+          e.begin_adjacency()?.write_adjacency_edges(sorted.into_iter())?
+        },
+      }.end_edge_data()?;
+    }
+
+    let mut d = e.node_data();
+    if !self.display_coordinates.is_empty() {
+      d = d.begin_display_data()?.write_display_coordinates(self.display_coordinates.iter().copied())?;
+    }
+    if !self.demands.is_empty() {
+      d = d.begin_demands()?.write_demands(self.demands.iter().copied())?;
+    }
+    if !self.depots.is_empty() {
+      d = d.begin_depots()?.write_depots(self.depots.iter().copied())?;
+    }
+
+    let mut f = d.fixed_edges();
+    if !self.fixed_edges.is_empty() {
+      f = f.begin_fixed_edges()?.write_fixed_node_pairs(self.fixed_edges.iter().copied())?.end_fixed_edges()?;
+    }
+
+    let w = f.finish();
+    for (keyword, lines) in &self.other_sections {
+      writeln!(w, "{keyword}")?;
+      for l in lines { writeln!(w, "{l}")?; }
+    }
+    writeln!(w, "EOF")?;
+    Ok(())
+  }
+
+  /// The problem in TSPLIB format, ready for [`crate::Solver::solve`].
+  pub fn to_tsplib(&self) -> crate::Result<Vec<u8>> {
+    let mut m = Vec::with_capacity(4096);
+    self.write_tsplib(&mut m)?;
+    Ok(m)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn round_trips_through_reader() {
+    let p = ProblemInstance::new(ProblemKind::CVRP, 3)
+      .name("round trip")
+      .capacity(10)
+      .coordinates_2d(EdgeWeightKind::EUC_2D, vec![[0., 0.], [3., 4.], [6., 8.]])
+      .demands(vec![0, 4, 5])
+      .depot(0)
+      .fixed_edge([1, 2])
+      .keyword("VEHICLES", 2);
+
+    let read: ProblemInstance = String::from_utf8(p.to_tsplib().unwrap()).unwrap().parse().unwrap();
+    assert_eq!(read, p);
+  }
+
+  #[test]
+  fn rejects_inconsistent_sections() {
+    let p = ProblemInstance::new(ProblemKind::TSP, 3)
+      .coordinates_2d(EdgeWeightKind::EUC_2D, vec![[0., 0.], [1., 1.]]);
+    assert!(p.validate().is_err());
+    assert!(ProblemInstance::new(ProblemKind::TSP, 2).depot(2).validate().is_err());
+  }
+}
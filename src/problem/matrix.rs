@@ -0,0 +1,62 @@
+use crate::{LkhError, Node};
+
+/// A square matrix of edge weights, stored row-major. `get(i, j)` is the cost of going
+/// from node `i` to node `j` (0-indexed).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Matrix {
+  dimension: usize,
+  weights: Vec<i64>,
+}
+
+impl Matrix {
+  /// A `dimension` x `dimension` matrix of zeroes.
+  pub fn new(dimension: usize) -> Self {
+    Self { dimension, weights: vec![0; dimension * dimension] }
+  }
+
+  pub fn from_fn(dimension: usize, f: impl Fn(usize, usize) -> i64) -> Self {
+    Self {
+      dimension,
+      weights: (0..dimension).flat_map(|i| (0..dimension).map(move |j| (i, j))).map(|(i, j)| f(i, j)).collect()
+    }
+  }
+
+  /// A matrix from its rows, which must all be as long as there are rows.
+  pub fn from_rows<R: AsRef<[i64]>>(rows: &[R]) -> crate::Result<Self> {
+    let dimension = rows.len();
+    if let Some((i, r)) = rows.iter().enumerate().find(|(_, r)| r.as_ref().len() != dimension) {
+      return Err(LkhError::invalid_problem(format!(
+        "matrix row {i} has {} entries, expected {dimension}", r.as_ref().len()
+      )))
+    }
+    Ok(Self { dimension, weights: rows.iter().flat_map(|r| r.as_ref().iter().copied()).collect() })
+  }
+
+  pub fn dimension(&self) -> usize { self.dimension }
+
+  pub fn get(&self, i: usize, j: usize) -> i64 { self.weights[i * self.dimension + j] }
+
+  pub fn set(&mut self, i: usize, j: usize, weight: i64) { self.weights[i * self.dimension + j] = weight }
+
+  pub fn row(&self, i: usize) -> &[i64] { &self.weights[i * self.dimension..(i + 1) * self.dimension] }
+
+  /// All weights, row-major.
+  pub fn as_slice(&self) -> &[i64] { &self.weights }
+
+  pub fn is_symmetric(&self) -> bool {
+    (0..self.dimension).all(|i| (0..i).all(|j| self.get(i, j) == self.get(j, i)))
+  }
+
+  /// The cost of visiting `nodes` in order without returning to the first.
+  pub fn path_cost(&self, nodes: &[Node]) -> i64 {
+    nodes.windows(2).map(|w| self.get(w[0] as usize, w[1] as usize)).sum()
+  }
+
+  /// The cost of the closed tour through `nodes`, back to the first.
+  pub fn tour_cost(&self, nodes: &[Node]) -> i64 {
+    match (nodes.first(), nodes.last()) {
+      (Some(&first), Some(&last)) => self.path_cost(nodes) + self.get(last as usize, first as usize),
+      _ => 0,
+    }
+  }
+}
@@ -1,10 +1,15 @@
 use std::io::Write;
-use self::header::Header;
+use self::{coordinates::Coordinates, edge_weights::EdgeWeights, edges::EdgeData, fixed_edges::FixedEdges, header::Header, node_data::NodeData};
 
 pub mod header;
 pub mod edges;
+pub mod edge_weights;
 pub mod coordinates;
 pub mod fixed_edges;
+pub mod instance;
+pub mod matrix;
+pub mod node_data;
+pub mod reader;
 
 #[derive(Default)]
 pub struct Problem<W = Vec<u8>>(W) where W: Write;
@@ -12,4 +17,12 @@ pub struct Problem<W = Vec<u8>>(W) where W: Write;
 impl<W: Write> Problem<W> {
   pub fn with(w: W) -> Self { Self(w) }
   pub fn header(self) -> Header<W> { Header::new(self.0) }
+
+  // Sections may appear in any order; these start one directly, e.g. after the `finish`
+  // of another section.
+  pub fn coords(self) -> Coordinates<W> { Coordinates::new(self.0) }
+  pub fn edge_weights(self) -> EdgeWeights<W> { EdgeWeights::new(self.0) }
+  pub fn edges(self) -> EdgeData<W> { EdgeData::new(self.0) }
+  pub fn fixed_edges(self) -> FixedEdges<W> { FixedEdges::new(self.0) }
+  pub fn node_data(self) -> NodeData<W> { NodeData::new(self.0) }
 }
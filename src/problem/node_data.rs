@@ -0,0 +1,230 @@
+use std::io::Write;
+use crate::{edges::EdgeData, fixed_edges::FixedEdges, util, Node};
+
+/// One line of a `PICKUP_AND_DELIVERY_SECTION`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PickupAndDelivery {
+  /// Positive at a pickup, negative at a delivery
+  pub demand: i64,
+  /// `[earliest, latest]` start of service
+  pub time_window: [i64; 2],
+  pub service_time: i64,
+  /// For a delivery, the node its goods are picked up at
+  pub pickup: Option<Node>,
+  /// For a pickup, the node its goods are delivered to
+  pub delivery: Option<Node>,
+}
+
+/// Sections which attach a value (or a membership) to individual nodes.
+pub struct NodeData<W: Write>(W);
+
+impl<W: Write> NodeData<W> {
+  #[inline(always)] #[must_use]
+  pub(crate) const fn new(w: W) -> Self { Self(w) }
+
+  pub fn begin_display_data(
+    mut self,
+  ) -> crate::Result<Self> {
+    writeln!(self.0, "DISPLAY_DATA_SECTION")?; Ok(self)
+  }
+
+  /// Coordinates used only for drawing the nodes, one per node in order.
+  pub fn write_display_coordinates(
+    mut self,
+    coordinates: impl Iterator<Item = [f32; 2]>
+  ) -> crate::Result<Self> {
+    for (i, [x, y]) in coordinates.enumerate() {
+      writeln!(self.0, "{} {:.10e} {:.10e}", i + 1, x, y)?; // LKH is 1-indexed
+    }
+    Ok(self)
+  }
+
+  pub fn begin_demands(
+    mut self,
+  ) -> crate::Result<Self> {
+    writeln!(self.0, "DEMAND_SECTION")?; Ok(self)
+  }
+
+  /// The demand of every node, in order. Depots usually have demand 0.
+  pub fn write_demands(
+    mut self,
+    demands: impl Iterator<Item = i64>
+  ) -> crate::Result<Self> {
+    for (i, d) in demands.enumerate() {
+      writeln!(self.0, "{} {}", i + 1, d)?;
+    }
+    Ok(self)
+  }
+
+  pub fn begin_depots(
+    mut self,
+  ) -> crate::Result<Self> {
+    writeln!(self.0, "DEPOT_SECTION")?; Ok(self)
+  }
+
+  /// Writes the depot nodes and closes the `DEPOT_SECTION`.
+  pub fn write_depots(
+    mut self,
+    depots: impl Iterator<Item = Node>
+  ) -> crate::Result<Self> {
+    for d in depots {
+      writeln!(self.0, "{}", d + 1)?;
+    }
+    util::end_seq(&mut self.0)?;
+    Ok(self)
+  }
+
+  pub fn begin_node_scores(
+    mut self,
+  ) -> crate::Result<Self> {
+    writeln!(self.0, "NODE_SCORE_SECTION")?; Ok(self)
+  }
+
+  /// The score (prize) collected by visiting every node, in order.
+  pub fn write_node_scores(
+    mut self,
+    scores: impl Iterator<Item = i64>
+  ) -> crate::Result<Self> {
+    for (i, s) in scores.enumerate() {
+      writeln!(self.0, "{} {}", i + 1, s)?;
+    }
+    Ok(self)
+  }
+
+  pub fn begin_time_windows(
+    mut self,
+  ) -> crate::Result<Self> {
+    writeln!(self.0, "TIME_WINDOW_SECTION")?; Ok(self)
+  }
+
+  /// The `[earliest, latest]` time service may start at every node, in order.
+  pub fn write_time_windows(
+    mut self,
+    windows: impl Iterator<Item = [i64; 2]>
+  ) -> crate::Result<Self> {
+    for (i, [earliest, latest]) in windows.enumerate() {
+      writeln!(self.0, "{} {} {}", i + 1, earliest, latest)?;
+    }
+    Ok(self)
+  }
+
+  pub fn begin_service_times(
+    mut self,
+  ) -> crate::Result<Self> {
+    writeln!(self.0, "SERVICE_TIME_SECTION")?; Ok(self)
+  }
+
+  /// How long serving every node takes, in order.
+  pub fn write_service_times(
+    mut self,
+    times: impl Iterator<Item = i64>
+  ) -> crate::Result<Self> {
+    for (i, t) in times.enumerate() {
+      writeln!(self.0, "{} {}", i + 1, t)?;
+    }
+    Ok(self)
+  }
+
+  /// The amounts picked up and delivered at every node of a VRPSPD, in order, as
+  /// `[pickup, delivery]`. These share the section of [`NodeData::write_pickup_and_delivery`],
+  /// with the amounts in place of the sibling nodes and an unbounded time window.
+  pub fn write_pickup_and_delivery_amounts(
+    mut self,
+    amounts: impl Iterator<Item = [i64; 2]>
+  ) -> crate::Result<Self> {
+    for (i, [pickup, delivery]) in amounts.enumerate() {
+      writeln!(self.0, "{} {} 0 {} 0 {} {}", i + 1, delivery, i32::MAX, pickup, delivery)?;
+    }
+    Ok(self)
+  }
+
+  pub fn begin_backhauls(
+    mut self,
+  ) -> crate::Result<Self> {
+    writeln!(self.0, "BACKHAUL_SECTION")?; Ok(self)
+  }
+
+  /// Writes the backhaul nodes and closes the `BACKHAUL_SECTION`.
+  pub fn write_backhauls(
+    mut self,
+    backhauls: impl Iterator<Item = Node>
+  ) -> crate::Result<Self> {
+    for v in backhauls {
+      writeln!(self.0, "{}", v + 1)?;
+    }
+    util::end_seq(&mut self.0)?;
+    Ok(self)
+  }
+
+  pub fn begin_required_nodes(
+    mut self,
+  ) -> crate::Result<Self> {
+    writeln!(self.0, "REQUIRED_NODES_SECTION")?; Ok(self)
+  }
+
+  /// Writes the nodes a Steiner tour must visit and closes the `REQUIRED_NODES_SECTION`.
+  pub fn write_required_nodes(
+    mut self,
+    nodes: impl Iterator<Item = Node>
+  ) -> crate::Result<Self> {
+    for v in nodes {
+      writeln!(self.0, "{}", v + 1)?;
+    }
+    util::end_seq(&mut self.0)?;
+    Ok(self)
+  }
+
+  pub fn begin_gtsp_sets(
+    mut self,
+  ) -> crate::Result<Self> {
+    writeln!(self.0, "GTSP_SET_SECTION")?; Ok(self)
+  }
+
+  pub fn begin_ctsp_sets(
+    mut self,
+  ) -> crate::Result<Self> {
+    writeln!(self.0, "CTSP_SET_SECTION")?; Ok(self)
+  }
+
+  /// Writes each set (of a `GTSP_SET_SECTION` or `CTSP_SET_SECTION`) as its 1-indexed id
+  /// followed by its nodes.
+  pub fn write_sets<S: AsRef<[Node]>>(
+    mut self,
+    sets: impl Iterator<Item = S>
+  ) -> crate::Result<Self> {
+    for (i, set) in sets.enumerate() {
+      write!(self.0, "{} ", i + 1)?;
+      for v in set.as_ref() { write!(self.0, "{} ", v + 1)?; }
+      util::end_seq(&mut self.0)?;
+    }
+    Ok(self)
+  }
+
+  pub fn begin_pickup_and_delivery(
+    mut self,
+  ) -> crate::Result<Self> {
+    writeln!(self.0, "PICKUP_AND_DELIVERY_SECTION")?; Ok(self)
+  }
+
+  /// One entry per node, in order.
+  pub fn write_pickup_and_delivery(
+    mut self,
+    entries: impl Iterator<Item = PickupAndDelivery>
+  ) -> crate::Result<Self> {
+    let index = |v: Option<Node>| v.map_or(0, |v| v + 1); // 0 is "none"
+    for (i, e) in entries.enumerate() {
+      let [earliest, latest] = e.time_window;
+      writeln!(
+        self.0, "{} {} {} {} {} {} {}",
+        i + 1, e.demand, earliest, latest, e.service_time, index(e.pickup), index(e.delivery)
+      )?;
+    }
+    Ok(self)
+  }
+
+  pub fn edges(self) -> EdgeData<W> { EdgeData::new(self.0) }
+
+  pub fn fixed_edges(self) -> FixedEdges<W> { FixedEdges::new(self.0) }
+
+  pub fn finish(self) -> W { self.0 }
+}
@@ -0,0 +1,425 @@
+use std::{io::BufRead, path::Path, str::FromStr};
+
+use crate::{
+  header::{CoordinateKind, EdgeFormat, EdgeWeightFormat, ProblemKind},
+  instance::{NodeCoordinates, ProblemInstance},
+  matrix::Matrix,
+  LkhError, Node,
+};
+
+/// Reads `.tsp`, `.atsp`, `.sop`, `.hcp` and `.vrp` files in TSPLIB format.
+pub struct Reader {
+  lines: Vec<String>,
+  /// 0-indexed position of the next line to read
+  at: usize,
+}
+
+fn error(line: usize, message: impl std::fmt::Display) -> LkhError {
+  LkhError::invalid_problem(format!("line {line}: {message}"))
+}
+
+fn is_keyword(line: &str) -> bool {
+  line.trim_start().starts_with(|c: char| c.is_ascii_alphabetic())
+}
+
+impl Reader {
+  pub fn new(r: impl BufRead) -> crate::Result<Self> {
+    Ok(Self { lines: r.lines().collect::<std::io::Result<_>>()?, at: 0 })
+  }
+
+  /// The 1-indexed number of the line most recently read.
+  fn line(&self) -> usize { self.at }
+
+  fn number<T: FromStr>(&self, token: &str, what: &str) -> crate::Result<T> {
+    token.parse().map_err(|_| error(self.line(), format!("{what} is not a number: {token:?}")))
+  }
+
+  fn node(&self, token: &str, dimension: u32) -> crate::Result<Node> {
+    match self.number::<Node>(token, "node")? {
+      n @ 1.. if n <= dimension => Ok(n - 1), // LKH is 1-indexed
+      n => Err(error(self.line(), format!("node {n} is outside 1..={dimension}")))
+    }
+  }
+
+  /// The lines of a section: everything up to the next keyword (or the end of input).
+  fn section_lines(&mut self) -> Vec<(usize, String)> {
+    let mut lines = vec![];
+    while let Some(line) = self.lines.get(self.at) {
+      if is_keyword(line) { break }
+      self.at += 1;
+      if !line.trim().is_empty() { lines.push((self.at, line.clone())) }
+    }
+    lines
+  }
+
+  /// The lines of a section giving one line per node, checked against DIMENSION before
+  /// anything is allocated for it.
+  fn node_lines(&mut self, section: &str, dimension: u32) -> crate::Result<Vec<(usize, String)>> {
+    let start = self.line();
+    let lines = self.section_lines().into_iter().filter(|(_, l)| l.trim() != "-1").collect::<Vec<_>>();
+    match lines.len() == dimension as usize {
+      true => Ok(lines),
+      false => Err(error(start, format!("{section} has {} nodes, expected {dimension}", lines.len()))),
+    }
+  }
+
+  /// The whitespace separated tokens of a section, stopping at the first `-1` if `terminated`.
+  fn section_tokens(&mut self, terminated: bool) -> Vec<(usize, String)> {
+    let mut tokens = vec![];
+    for (n, line) in self.section_lines() {
+      for t in line.split_whitespace() {
+        if terminated && t == "-1" { return tokens }
+        tokens.push((n, t.to_owned()));
+      }
+    }
+    tokens
+  }
+
+  fn at_line<T>(&mut self, line: usize, f: impl FnOnce(&Self) -> crate::Result<T>) -> crate::Result<T> {
+    let saved = std::mem::replace(&mut self.at, line);
+    let r = f(self);
+    self.at = saved;
+    r
+  }
+
+  pub fn read(mut self) -> crate::Result<ProblemInstance> {
+    let mut p = ProblemInstance::default();
+
+    while let Some(line) = self.lines.get(self.at).cloned() {
+      self.at += 1;
+      let line = line.trim();
+      if line.is_empty() { continue }
+
+      let (keyword, value) = match line.split_once(':') {
+        Some((k, v)) => (k.trim(), v.trim()),
+        None => line.split_once(char::is_whitespace).map(|(k, v)| (k, v.trim())).unwrap_or((line, "")),
+      };
+      let first = value.split_whitespace().next().unwrap_or("");
+
+      match keyword {
+        "EOF" => break,
+        "NAME" => p.name = Some(value.to_owned()),
+        "TYPE" => p.kind = Some(first.parse().map_err(|e| error(self.line(), e))?),
+        "COMMENT" => p.comments.push(value.to_owned()),
+        "DIMENSION" => p.dimension = self.number(first, "DIMENSION")?,
+        "CAPACITY" => p.capacity = Some(self.number(first, "CAPACITY")?),
+        "EDGE_WEIGHT_TYPE" => p.edge_weight_kind = Some(first.parse().map_err(|e| error(self.line(), e))?),
+        "EDGE_WEIGHT_FORMAT" => p.edge_weight_format = Some(first.parse().map_err(|e| error(self.line(), e))?),
+        "EDGE_DATA_FORMAT" => p.edge_data_format = Some(first.parse().map_err(|e| error(self.line(), e))?),
+        "NODE_COORD_TYPE" => p.node_coord_kind = Some(first.parse().map_err(|e| error(self.line(), e))?),
+        "DISPLAY_DATA_TYPE" => p.display_data_kind = Some(first.parse().map_err(|e| error(self.line(), e))?),
+        "NODE_COORD_SECTION" => self.node_coord_section(&mut p)?,
+        "EDGE_WEIGHT_SECTION" => self.edge_weight_section(&mut p)?,
+        "EDGE_DATA_SECTION" => self.edge_data_section(&mut p)?,
+        "FIXED_EDGES_SECTION" => self.fixed_edges_section(&mut p)?,
+        "DISPLAY_DATA_SECTION" => self.display_data_section(&mut p)?,
+        "DEMAND_SECTION" => self.demand_section(&mut p)?,
+        "DEPOT_SECTION" => self.depot_section(&mut p)?,
+        k if k.ends_with("_SECTION") => {
+          let lines = self.section_lines().into_iter().map(|(_, l)| l.trim().to_owned()).collect();
+          p.other_sections.push((k.to_owned(), lines));
+        },
+        k => p.other_keywords.push((k.to_owned(), value.to_owned())),
+      }
+    }
+
+    Ok(p)
+  }
+
+  fn node_coord_section(&mut self, p: &mut ProblemInstance) -> crate::Result<()> {
+    let lines = self.node_lines("NODE_COORD_SECTION", p.dimension)?;
+    let n = p.dimension as usize;
+
+    let three_d = match (p.node_coord_kind, p.edge_weight_kind.and_then(|k| k.coordinate_dimension())) {
+      (Some(CoordinateKind::THREED_COORDS), _) | (None, Some(3)) => true,
+      (Some(_), _) | (None, Some(_)) => false,
+      (None, None) => lines.first().is_some_and(|(_, l)| l.split_whitespace().count() == 4),
+    };
+    let width = if three_d { 3 } else { 2 };
+
+    let mut coordinates = vec![[0f32; 3]; n];
+    let mut seen = vec![false; n];
+    for (line, text) in &lines {
+      self.at_line(*line, |r| {
+        let tokens = text.split_whitespace().collect::<Vec<_>>();
+        if tokens.len() != width + 1 {
+          return Err(error(*line, format!("expected a node and {width} coordinates")))
+        }
+        let node = r.node(tokens[0], p.dimension)? as usize;
+        if std::mem::replace(&mut seen[node], true) {
+          return Err(error(*line, format!("node {} already has coordinates", node + 1)))
+        }
+        for (k, t) in tokens[1..].iter().enumerate() {
+          coordinates[node][k] = r.number(t, "coordinate")?;
+        }
+        Ok(())
+      })?;
+    }
+
+    p.coordinates = Some(match three_d {
+      true => NodeCoordinates::ThreeD(coordinates),
+      false => NodeCoordinates::TwoD(coordinates.into_iter().map(|[x, y, _]| [x, y]).collect()),
+    });
+    Ok(())
+  }
+
+  fn edge_weight_section(&mut self, p: &mut ProblemInstance) -> crate::Result<()> {
+    let start = self.line();
+    let n = p.dimension as usize;
+    let format = p.edge_weight_format.unwrap_or(EdgeWeightFormat::FULL_MATRIX);
+    let expected = format.entry_count(n);
+
+    let mut tokens = self.section_tokens(false);
+    // SOP files repeat the dimension on the first line of the section
+    if p.kind == Some(ProblemKind::SOP) && tokens.len() == expected + 1 {
+      tokens.remove(0);
+    }
+    if tokens.len() != expected {
+      return Err(error(start, format!("EDGE_WEIGHT_SECTION has {} entries, expected {expected} for {format:?}", tokens.len())))
+    }
+
+    let mut m = Matrix::new(n);
+    for ((i, j), (line, t)) in format.entries(n).zip(&tokens) {
+      let w = self.at_line(*line, |r| r.number(t, "weight"))?;
+      m.set(i, j, w);
+      if format.is_triangular() { m.set(j, i, w); }
+    }
+
+    p.edge_weights = Some(m);
+    Ok(())
+  }
+
+  fn edge_data_section(&mut self, p: &mut ProblemInstance) -> crate::Result<()> {
+    match p.edge_data_format.unwrap_or_default() {
+      EdgeFormat::EDGE_LIST => {
+        let tokens = self.section_tokens(true);
+        // LKH-3 weighted edge lists (STTSP) give every edge a line of `a b weight`
+        let mut lines = tokens.chunk_by(|(a, _), (b, _)| a == b);
+        if !tokens.is_empty() && lines.all(|l| l.len() == 3) {
+          for edge in tokens.chunks(3) {
+            p.edges.extend(self.node_pairs(&edge[..2], p.dimension)?);
+            p.edge_data_weights.push(self.at_line(edge[2].0, |r| r.number(&edge[2].1, "weight"))?);
+          }
+        } else {
+          p.edges = self.node_pairs(&tokens, p.dimension)?;
+        }
+      },
+      EdgeFormat::ADJ_LIST => {
+        // `source neighbour... -1` per line, the whole section closed by a final -1
+        for (line, text) in self.section_lines() {
+          self.at_line(line, |r| {
+            let mut tokens = text.split_whitespace().take_while(|t| *t != "-1");
+            let Some(source) = tokens.next() else { return Ok(()) };
+            let source = r.node(source, p.dimension)?;
+            for t in tokens {
+              p.edges.push([source, r.node(t, p.dimension)?]);
+            }
+            Ok(())
+          })?;
+        }
+      },
+    }
+    Ok(())
+  }
+
+  fn node_pairs(&mut self, tokens: &[(usize, String)], dimension: u32) -> crate::Result<Vec<[Node; 2]>> {
+    if !tokens.len().is_multiple_of(2) {
+      let line = tokens.last().map(|(l, _)| *l).unwrap_or(self.line());
+      return Err(error(line, "edge list has an odd number of nodes"))
+    }
+    tokens
+      .chunks(2)
+      .map(|pair| {
+        let a = self.at_line(pair[0].0, |r| r.node(&pair[0].1, dimension))?;
+        let b = self.at_line(pair[1].0, |r| r.node(&pair[1].1, dimension))?;
+        Ok([a, b])
+      })
+      .collect()
+  }
+
+  fn fixed_edges_section(&mut self, p: &mut ProblemInstance) -> crate::Result<()> {
+    let tokens = self.section_tokens(true);
+    p.fixed_edges = self.node_pairs(&tokens, p.dimension)?;
+    Ok(())
+  }
+
+  fn display_data_section(&mut self, p: &mut ProblemInstance) -> crate::Result<()> {
+    let lines = self.node_lines("DISPLAY_DATA_SECTION", p.dimension)?;
+    let mut display = vec![[0f32; 2]; p.dimension as usize];
+    for (line, text) in lines {
+      self.at_line(line, |r| {
+        let tokens = text.split_whitespace().collect::<Vec<_>>();
+        let [node, x, y] = tokens[..] else { return Err(error(line, "expected a node and 2 coordinates")) };
+        display[r.node(node, p.dimension)? as usize] = [r.number(x, "coordinate")?, r.number(y, "coordinate")?];
+        Ok(())
+      })?;
+    }
+    p.display_coordinates = display;
+    Ok(())
+  }
+
+  fn demand_section(&mut self, p: &mut ProblemInstance) -> crate::Result<()> {
+    let lines = self.node_lines("DEMAND_SECTION", p.dimension)?;
+    let mut demands = vec![0; p.dimension as usize];
+    for (line, text) in lines {
+      self.at_line(line, |r| {
+        let tokens = text.split_whitespace().collect::<Vec<_>>();
+        let [node, demand] = tokens[..] else { return Err(error(line, "expected a node and its demand")) };
+        demands[r.node(node, p.dimension)? as usize] = r.number(demand, "demand")?;
+        Ok(())
+      })?;
+    }
+    p.demands = demands;
+    Ok(())
+  }
+
+  fn depot_section(&mut self, p: &mut ProblemInstance) -> crate::Result<()> {
+    for (line, t) in self.section_tokens(true) {
+      let depot = self.at_line(line, |r| r.node(&t, p.dimension))?;
+      p.depots.push(depot);
+    }
+    Ok(())
+  }
+}
+
+impl ProblemInstance {
+  /// Reads a TSPLIB problem.
+  pub fn read(r: impl BufRead) -> crate::Result<Self> {
+    Reader::new(r)?.read()
+  }
+
+  /// Reads the TSPLIB problem file at `path`. To solve a file as it is, without reading
+  /// it, see [`crate::Solver::solve_file`].
+  pub fn from_file(path: impl AsRef<Path>) -> crate::Result<Self> {
+    let file = std::fs::File::open(path)?;
+    Self::read(std::io::BufReader::new(file))
+  }
+}
+
+impl FromStr for ProblemInstance {
+  type Err = LkhError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> { Self::read(s.as_bytes()) }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::header::EdgeWeightKind;
+
+  #[test]
+  fn reads_coordinates_and_fixed_edges() {
+    let p: ProblemInstance = "\
+NAME : square
+TYPE : TSP
+COMMENT : four corners
+DIMENSION : 4
+EDGE_WEIGHT_TYPE : EUC_2D
+NODE_COORD_SECTION
+1 0 0
+2 10 0
+3 10 10
+4 0 10
+FIXED_EDGES_SECTION
+1 2
+-1
+EOF
+".parse().unwrap();
+
+    assert_eq!(p.name.as_deref(), Some("square"));
+    assert_eq!(p.kind, Some(ProblemKind::TSP));
+    assert_eq!(p.edge_weight_kind, Some(EdgeWeightKind::EUC_2D));
+    assert_eq!(p.coordinates, Some(NodeCoordinates::TwoD(vec![[0., 0.], [10., 0.], [10., 10.], [0., 10.]])));
+    assert_eq!(p.fixed_edges, vec![[0, 1]]);
+  }
+
+  #[test]
+  fn expands_every_weight_format() {
+    let full = Matrix::from_rows(&[[0, 1, 2], [1, 0, 3], [2, 3, 0]]).unwrap();
+
+    for format in [
+      EdgeWeightFormat::FULL_MATRIX, EdgeWeightFormat::UPPER_ROW, EdgeWeightFormat::LOWER_ROW,
+      EdgeWeightFormat::UPPER_DIAG_ROW, EdgeWeightFormat::LOWER_DIAG_ROW, EdgeWeightFormat::UPPER_COL,
+      EdgeWeightFormat::LOWER_COL, EdgeWeightFormat::UPPER_DIAG_COL, EdgeWeightFormat::LOWER_DIAG_COL,
+    ] {
+      let weights = format.entries(3).map(|(i, j)| full.get(i, j).to_string()).collect::<Vec<_>>().join(" ");
+      let p: ProblemInstance = format!(
+        "TYPE: TSP\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: {format:?}\nEDGE_WEIGHT_SECTION\n{weights}\nEOF\n"
+      ).parse().unwrap();
+      assert_eq!(p.edge_weights.as_ref(), Some(&full), "{format:?}");
+    }
+  }
+
+  #[test]
+  fn reads_vrp_sections() {
+    let p: ProblemInstance = "\
+TYPE : CVRP
+DIMENSION : 3
+CAPACITY : 10
+EDGE_WEIGHT_TYPE : EUC_2D
+NODE_COORD_SECTION
+1 0 0
+2 3 4
+3 6 8
+DEMAND_SECTION
+1 0
+2 4
+3 5
+DEPOT_SECTION
+ 1
+ -1
+EOF
+".parse().unwrap();
+
+    assert_eq!(p.capacity, Some(10));
+    assert_eq!(p.demands, vec![0, 4, 5]);
+    assert_eq!(p.depots, vec![0]);
+  }
+
+  #[test]
+  fn keeps_lkh3_sections() {
+    let p: ProblemInstance = "\
+TYPE : STTSP
+DIMENSION : 3
+EDGE_WEIGHT_TYPE : EXPLICIT
+EDGE_DATA_FORMAT : EDGE_LIST
+REQUIRED_NODES_SECTION
+1
+3
+-1
+EDGE_DATA_SECTION
+1 2 5
+2 3 7
+-1
+EOF
+".parse().unwrap();
+
+    assert_eq!(p.edges, vec![[0, 1], [1, 2]]);
+    assert_eq!(p.edge_data_weights, vec![5, 7]);
+    assert_eq!(p.other_section("REQUIRED_NODES_SECTION"), Some(&["1", "3", "-1"].map(String::from)[..]));
+    assert_eq!(String::from_utf8(p.to_tsplib().unwrap()).unwrap().parse::<ProblemInstance>().unwrap(), p);
+  }
+
+  #[test]
+  fn rejects_sections_short_of_dimension() {
+    for section in ["NODE_COORD_SECTION\n1 0 0\n2 1 1", "DEMAND_SECTION\n1 0\n2 4", "DISPLAY_DATA_SECTION\n1 0 0\n2 1 1"] {
+      let e = format!("DIMENSION : 4000000000\nEDGE_WEIGHT_TYPE : EUC_2D\n{section}\nEOF\n").parse::<ProblemInstance>();
+      assert!(matches!(e, Err(LkhError::InvalidProblem(m)) if m.starts_with("line 3")), "{section}");
+    }
+  }
+
+  #[test]
+  fn rejects_duplicate_coordinates() {
+    let e = "DIMENSION : 2\nEDGE_WEIGHT_TYPE : EUC_2D\nNODE_COORD_SECTION\n1 0 0\n1 1 1\n".parse::<ProblemInstance>();
+    assert!(matches!(e, Err(LkhError::InvalidProblem(m)) if m.starts_with("line 5")));
+  }
+
+  #[test]
+  fn reports_line_numbers() {
+    let e = "DIMENSION : 2\nEDGE_WEIGHT_TYPE : EUC_2D\nNODE_COORD_SECTION\n1 0 0\n3 1 1\n".parse::<ProblemInstance>();
+    match e {
+      Err(LkhError::InvalidProblem(m)) => assert!(m.starts_with("line 5"), "{m}"),
+      other => panic!("unexpected {other:?}"),
+    }
+  }
+}
@@ -0,0 +1,255 @@
+use std::str::FromStr;
+
+/// A tour cost as printed by LKH. LKH-3 prints constrained problems as `penalty_cost`;
+/// plain LKH-2 costs have no penalty. Costs compare by penalty first, as in LKH-3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cost {
+  pub penalty: Option<i64>,
+  pub value: i64,
+}
+
+impl Cost {
+  /// A solution is feasible if it has no (or zero) penalty.
+  pub fn is_feasible(&self) -> bool { self.penalty.unwrap_or(0) == 0 }
+}
+
+impl FromStr for Cost {
+  type Err = std::num::ParseIntError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.split_once('_') {
+      Some((penalty, value)) => Ok(Self { penalty: Some(penalty.parse()?), value: value.parse()? }),
+      None => Ok(Self { penalty: None, value: s.parse()? })
+    }
+  }
+}
+
+/// The result of a single run (`Run n: Cost = ..., Time = ... sec.`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunRecord {
+  pub run: u32,
+  pub cost: Cost,
+  /// Percentage above `OPTIMUM`, when one was given
+  pub gap: Option<f64>,
+  /// Seconds
+  pub time: f64,
+}
+
+/// A `.min`, `.avg`, `.max` triple from LKH's summary.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Statistic<T> {
+  pub min: T,
+  pub avg: f64,
+  pub max: T,
+}
+
+/// Solution quality and timings, parsed from LKH's standard output.
+///
+/// Anything LKH did not print (it depends on `TRACE_LEVEL`, `OPTIMUM` and the LKH version)
+/// is left as `None`. Times are in seconds, gaps are percentages.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SolveReport {
+  pub version: Option<String>,
+  pub lower_bound: Option<f64>,
+  /// Gap between the lower bound and `OPTIMUM`
+  pub lower_bound_gap: Option<f64>,
+  pub ascent_time: Option<f64>,
+  pub preprocessing_time: Option<f64>,
+  pub runs: Vec<RunRecord>,
+  /// `Successes/Runs`: runs reaching `OPTIMUM`, total runs
+  pub successes: Option<(u32, u32)>,
+  pub cost: Option<Statistic<Cost>>,
+  pub gap: Option<Statistic<f64>>,
+  pub trials: Option<Statistic<u64>>,
+  pub time: Option<Statistic<f64>>,
+  pub total_time: Option<f64>,
+}
+
+/// Splits `Key = value, Key = value` into pairs, dropping units such as `sec.` and `%`.
+fn fields(line: &str) -> impl Iterator<Item = (&str, &str)> {
+  line
+    .split(',')
+    .filter_map(|f| f.split_once('='))
+    .map(|(k, v)| {
+      let v = v.split_whitespace().next().unwrap_or("");
+      (k.trim(), v.trim_end_matches('%'))
+    })
+}
+
+fn field<T: FromStr>(line: &str, key: &str) -> Option<T> {
+  fields(line).find(|(k, _)| *k == key).and_then(|(_, v)| v.parse().ok())
+}
+
+fn statistic<T: FromStr + Copy>(line: &str, key: &str) -> Option<Statistic<T>> {
+  Some(Statistic {
+    min: field(line, &format!("{key}.min"))?,
+    avg: field(line, &format!("{key}.avg"))?,
+    max: field(line, &format!("{key}.max"))?,
+  })
+}
+
+/// Parses the `n: Cost = ..., Time = ...` tail shared by `Run n:` and `* n:` lines.
+pub(crate) fn numbered_cost(rest: &str) -> Option<(u32, Cost, Option<f64>, Option<f64>)> {
+  let (n, rest) = rest.split_once(':')?;
+  Some((n.trim().parse().ok()?, field(rest, "Cost")?, field(rest, "Gap"), field(rest, "Time")))
+}
+
+/// Something LKH reported while it was running. Lines that are not recognized are
+/// passed through as [`ProgressEvent::Output`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ProgressEvent {
+  /// A step of the subgradient ascent (printed at `TRACE_LEVEL` 2 and above)
+  Ascent { t: u32, period: u32, best_w: f64, norm: i64 },
+  /// The ascent is complete
+  LowerBound { value: f64, ascent_time: Option<f64> },
+  /// Candidate sets are built and the search begins
+  Preprocessed { time: f64 },
+  /// A better tour was found in the given trial of the current run
+  Improvement { trial: u32, cost: Cost, time: Option<f64> },
+  RunFinished(RunRecord),
+  /// All runs are done and the final summary (`Successes/Runs = ...`) has begun
+  Summary,
+  Output(String),
+}
+
+impl ProgressEvent {
+  pub fn parse(line: &str) -> Self {
+    let line = line.trim();
+
+    let event = if let Some(rest) = line.strip_prefix("* T =").map(|r| format!("T ={r}")) {
+      (|| Some(Self::Ascent {
+        t: field(&rest, "T")?,
+        period: field(&rest, "Period")?,
+        best_w: field(&rest, "BestW")?,
+        norm: field(&rest, "Norm")?,
+      }))()
+    } else if let Some(rest) = line.strip_prefix("* ") {
+      numbered_cost(rest).map(|(trial, cost, _, time)| Self::Improvement { trial, cost, time })
+    } else if let Some(rest) = line.strip_prefix("Run ") {
+      numbered_cost(rest).map(|(run, cost, gap, time)| {
+        Self::RunFinished(RunRecord { run, cost, gap, time: time.unwrap_or_default() })
+      })
+    } else if line.starts_with("Lower bound") {
+      field(line, "Lower bound").map(|value| Self::LowerBound { value, ascent_time: field(line, "Ascent time") })
+    } else if line.starts_with("Preprocessing time") {
+      field(line, "Preprocessing time").map(|time| Self::Preprocessed { time })
+    } else if line.starts_with("Successes/Runs") {
+      Some(Self::Summary)
+    } else {
+      None
+    };
+
+    event.unwrap_or_else(|| Self::Output(line.to_owned()))
+  }
+}
+
+impl SolveReport {
+  pub fn parse(output: &str) -> Self {
+    let mut report = Self::default();
+    output.lines().for_each(|l| { report.feed(l); });
+    report
+  }
+
+  /// Folds one line of output into the report. Returns whether the line was recognized.
+  pub(crate) fn feed(&mut self, line: &str) -> bool {
+    let line = line.trim();
+
+    if self.version.is_none() && line.starts_with("LKH-") {
+      self.version = line.split_whitespace().next().map(str::to_owned);
+    } else if line.starts_with("Lower bound") {
+      self.lower_bound = field(line, "Lower bound");
+      self.lower_bound_gap = field(line, "Gap");
+      self.ascent_time = field(line, "Ascent time");
+    } else if line.starts_with("Preprocessing time") {
+      self.preprocessing_time = field(line, "Preprocessing time");
+    } else if let Some(rest) = line.strip_prefix("Run ") {
+      let Some((run, cost, gap, time)) = numbered_cost(rest) else { return false };
+      self.runs.push(RunRecord { run, cost, gap, time: time.unwrap_or_default() });
+    } else if let Some(rest) = line.strip_prefix("Successes/Runs =") {
+      let (s, r) = rest.trim().split_once('/').unwrap_or_default();
+      self.successes = s.parse().ok().zip(r.parse().ok());
+    } else if line.starts_with("Cost.min") {
+      self.cost = statistic(line, "Cost");
+    } else if line.starts_with("Gap.min") {
+      self.gap = statistic(line, "Gap");
+    } else if line.starts_with("Trials.min") {
+      self.trials = statistic(line, "Trials");
+    } else if line.starts_with("Time.min") {
+      self.time = statistic(line, "Time");
+    } else if line.starts_with("Time.total") {
+      self.total_time = field(line, "Time.total");
+    } else {
+      return false
+    }
+
+    true
+  }
+
+  /// The best cost over all runs.
+  pub fn best_cost(&self) -> Option<Cost> {
+    self.runs
+      .iter()
+      .map(|r| r.cost)
+      .min()
+      .or(self.cost.map(|c| c.min))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  const OUTPUT: &str = "\
+PARAMETER_FILE = pr2392.par
+Reading PROBLEM_FILE: \"pr2392.tsp\" ... done
+Lower bound = 373489.9, Gap = 0.02%, Ascent time = 20.0 sec.
+Cand.min = 2, Cand.avg = 5.0, Cand.max = 5
+Preprocessing time = 23.1 sec.
+* 1: Cost = 378224, Gap = 0.0508%, Time = 0.1 sec.
+Run 1: Cost = 378062, Gap = 0.0079%, Time = 3.0 sec.
+Run 2: Cost = 378032, Gap = 0.0000%, Time = 2.5 sec.
+Successes/Runs = 1/2
+Cost.min = 378032, Cost.avg = 378047.00, Cost.max = 378062
+Gap.min = 0.0000%, Gap.avg = 0.0040%, Gap.max = 0.0079%
+Trials.min = 1, Trials.avg = 80.5, Trials.max = 160
+Time.min = 2.50 sec., Time.avg = 2.75 sec., Time.max = 3.00 sec.
+Time.total = 51.27 sec.
+";
+
+  #[test]
+  fn parses_summary() {
+    let r = SolveReport::parse(OUTPUT);
+    assert_eq!(r.lower_bound, Some(373489.9));
+    assert_eq!(r.lower_bound_gap, Some(0.02));
+    assert_eq!(r.preprocessing_time, Some(23.1));
+    assert_eq!(r.runs.len(), 2);
+    assert_eq!(r.runs[1], RunRecord { run: 2, cost: Cost { penalty: None, value: 378032 }, gap: Some(0.), time: 2.5 });
+    assert_eq!(r.successes, Some((1, 2)));
+    assert_eq!(r.trials.map(|t| t.max), Some(160));
+    assert_eq!(r.time.map(|t| t.avg), Some(2.75));
+    assert_eq!(r.total_time, Some(51.27));
+    assert_eq!(r.best_cost().map(|c| c.value), Some(378032));
+  }
+
+  #[test]
+  fn parses_progress() {
+    assert_eq!(
+      ProgressEvent::parse("* T = 3, Period = 50, BestW = 7541.5, Norm = 12"),
+      ProgressEvent::Ascent { t: 3, period: 50, best_w: 7541.5, norm: 12 }
+    );
+    assert_eq!(
+      ProgressEvent::parse("* 7: Cost = 0_7542, Time = 0.02 sec."),
+      ProgressEvent::Improvement { trial: 7, cost: Cost { penalty: Some(0), value: 7542 }, time: Some(0.02) }
+    );
+    assert!(matches!(ProgressEvent::parse("Run 1: Cost = 7542, Time = 0.1 sec."), ProgressEvent::RunFinished(_)));
+    assert!(matches!(ProgressEvent::parse("Reading PROBLEM_FILE: ... done"), ProgressEvent::Output(_)));
+  }
+
+  #[test]
+  fn parses_penalty_costs() {
+    let r = SolveReport::parse("Run 1: Cost = 2_7542, Time = 0.01 sec.\nRun 2: Cost = 0_7600, Time = 0.01 sec.\n");
+    assert_eq!(r.best_cost(), Some(Cost { penalty: Some(0), value: 7600 }));
+    assert!(r.best_cost().unwrap().is_feasible());
+  }
+}
@@ -0,0 +1,233 @@
+use std::{ffi::{OsStr, OsString}, path::{Path, PathBuf}, process::{Command, Stdio}, time::Duration};
+
+use crate::{memory_file::TempFile, parameter::Parameters, report::{ProgressEvent, SolveReport}, LkhError, Node};
+
+pub use running::{CancelHandle, RunningSolve};
+
+mod running;
+#[cfg(feature = "tokio")]
+mod nonblocking;
+
+/// Executable names tried (in order) on `PATH` when `LKH_PATH` is not set.
+pub const EXECUTABLE_NAMES: [&str; 4] = ["lkh", "LKH", "LKH-3", "LKH-2"];
+
+/// Environment variable overriding the location of the LKH executable.
+pub const LKH_PATH: &str = "LKH_PATH";
+
+/// A tour found by LKH, together with what LKH reported about it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+  pub tour: Vec<Node>,
+  pub report: SolveReport,
+}
+
+/// A configured LKH installation. A `Solver` is cheap to clone, holds no open resources,
+/// and may be shared between threads; every solve gets its own temporary files.
+#[derive(Debug, Clone)]
+pub struct Solver {
+  executable: PathBuf,
+  working_directory: Option<PathBuf>,
+  environment: Vec<(OsString, OsString)>,
+  parameters: Parameters,
+  temp_directory: PathBuf,
+  timeout: Option<Duration>,
+}
+
+const _: () = {
+  const fn assert_send_sync<T: Send + Sync>() {}
+  assert_send_sync::<Solver>()
+};
+
+impl Solver {
+  /// Locates LKH using `LKH_PATH`, falling back to searching `PATH` for [`EXECUTABLE_NAMES`].
+  pub fn new() -> crate::Result<Self> {
+    if let Some(path) = std::env::var_os(LKH_PATH) {
+      let path = PathBuf::from(path);
+      return match path.is_file() {
+        true => Ok(Self::with_executable(path)),
+        false => Err(LkhError::SolverNotFound { path })
+      }
+    }
+
+    std::env::var_os("PATH")
+      .iter()
+      .flat_map(std::env::split_paths)
+      .flat_map(|dir| {
+        EXECUTABLE_NAMES
+          .iter()
+          .map(move |name| dir.join(format!("{name}{}", std::env::consts::EXE_SUFFIX)))
+      })
+      .find(|candidate| candidate.is_file())
+      .map(Self::with_executable)
+      .ok_or_else(|| LkhError::SolverNotFound { path: EXECUTABLE_NAMES[0].into() })
+  }
+
+  /// Uses the executable at `path` without checking that it exists.
+  pub fn with_executable(path: impl Into<PathBuf>) -> Self {
+    Self {
+      executable: path.into(),
+      working_directory: None,
+      environment: vec![],
+      parameters: Parameters::default(),
+      temp_directory: std::env::temp_dir(),
+      timeout: None,
+    }
+  }
+
+  pub fn executable(&self) -> &Path { &self.executable }
+
+  /// Parameters used by [`Solver::solve`]. `PROBLEM_FILE` and `TOUR_FILE` are always
+  /// replaced by the solver's own temporary files.
+  pub fn default_parameters(&self) -> &Parameters { &self.parameters }
+
+  /// The directory LKH is started in.
+  pub fn working_directory(mut self, dir: impl Into<PathBuf>) -> Self {
+    self.working_directory = Some(dir.into()); self
+  }
+
+  /// Sets an environment variable for the LKH process.
+  pub fn env(mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> Self {
+    self.environment.push((key.as_ref().to_owned(), value.as_ref().to_owned())); self
+  }
+
+  pub fn parameters(mut self, parameters: Parameters) -> Self {
+    self.parameters = parameters; self
+  }
+
+  /// The directory problem, parameter and tour files are written to.
+  pub fn temp_directory(mut self, dir: impl Into<PathBuf>) -> Self {
+    self.temp_directory = dir.into(); self
+  }
+
+  /// Wall-clock limit applied to every solve started by this solver. A solve still running
+  /// after `timeout` is killed and finishes with [`LkhError::TimedOut`].
+  pub fn timeout(mut self, timeout: Duration) -> Self {
+    self.timeout = Some(timeout); self
+  }
+
+  pub(crate) fn temp_file(&self, extension: &str) -> TempFile {
+    TempFile::new(&self.temp_directory, extension)
+  }
+
+  /// Solves a problem already serialized in TSPLIB format (e.g. by [`crate::Problem`])
+  /// using the default parameters.
+  pub fn solve(&self, problem: impl AsRef<[u8]>) -> crate::Result<Solution> {
+    self.solve_with(problem, &self.parameters)
+  }
+
+  /// As [`Solver::solve`], with `parameters` instead of the defaults.
+  pub fn solve_with(
+    &self,
+    problem: impl AsRef<[u8]>,
+    parameters: &Parameters
+  ) -> crate::Result<Solution> {
+    self.spawn(problem, parameters)?.finish()
+  }
+
+  /// As [`Solver::solve_with`], calling `progress` for every line LKH prints while it runs.
+  pub fn solve_with_progress(
+    &self,
+    problem: impl AsRef<[u8]>,
+    parameters: &Parameters,
+    mut progress: impl FnMut(&ProgressEvent)
+  ) -> crate::Result<Solution> {
+    let mut running = self.spawn(problem, parameters)?;
+    for event in &mut running { progress(&event?); }
+    running.finish()
+  }
+
+  /// Solves the TSPLIB problem stored at `problem_file`.
+  pub fn solve_file(
+    &self,
+    problem_file: &Path,
+    parameters: &Parameters
+  ) -> crate::Result<Solution> {
+    self.spawn_file(problem_file, parameters)?.finish()
+  }
+
+  /// Starts LKH on a serialized problem without waiting for it.
+  pub fn spawn(
+    &self,
+    problem: impl AsRef<[u8]>,
+    parameters: &Parameters
+  ) -> crate::Result<RunningSolve> {
+    let problem_file = self.temp_file("tsp");
+    std::fs::write(problem_file.path(), problem)?;
+    self.start(&problem_file.path().clone(), parameters, Some(problem_file))
+  }
+
+  /// Starts LKH on the problem stored at `problem_file` without waiting for it.
+  pub fn spawn_file(
+    &self,
+    problem_file: &Path,
+    parameters: &Parameters
+  ) -> crate::Result<RunningSolve> {
+    self.start(problem_file, parameters, None)
+  }
+
+  fn start(
+    &self,
+    problem_path: &Path,
+    parameters: &Parameters,
+    problem_file: Option<TempFile>
+  ) -> crate::Result<RunningSolve> {
+    let tour_file = self.temp_file("tour");
+    let parameter_file = self.write_parameters(problem_path, &tour_file, parameters)?;
+
+    let child = 
+      self.command(parameter_file.path())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| self.spawn_error(e))?;
+
+    let running = RunningSolve::new(child, tour_file, parameter_file, problem_file);
+    Ok(match self.timeout {
+      Some(t) => running.timeout(t),
+      None => running
+    })
+  }
+
+  /// The parameter file for solving `problem_path` into `tour_file`.
+  pub(crate) fn parameter_contents(
+    &self,
+    problem_path: &Path,
+    tour_file: &TempFile,
+    parameters: &Parameters
+  ) -> crate::Result<Vec<u8>> {
+    let mut m = Vec::with_capacity(4096);
+    parameters
+      .clone()
+      .problem_file(problem_path)
+      .tour_file(tour_file.path())
+      .write(&mut m)?;
+    Ok(m)
+  }
+
+  pub(crate) fn write_parameters(
+    &self,
+    problem_path: &Path,
+    tour_file: &TempFile,
+    parameters: &Parameters
+  ) -> crate::Result<TempFile> {
+    let parameter_file = self.temp_file("par");
+    std::fs::write(parameter_file.path(), self.parameter_contents(problem_path, tour_file, parameters)?)?;
+    Ok(parameter_file)
+  }
+
+  pub(crate) fn command(&self, parameter_file: &Path) -> Command {
+    let mut c = Command::new(&self.executable);
+    c.arg(parameter_file)
+      .envs(self.environment.iter().map(|(k, v)| (k, v)))
+      .stdin(Stdio::null());
+    if let Some(dir) = &self.working_directory { c.current_dir(dir); }
+    c
+  }
+
+  pub(crate) fn spawn_error(&self, e: std::io::Error) -> LkhError {
+    match e.kind() {
+      std::io::ErrorKind::NotFound => LkhError::SolverNotFound { path: self.executable.clone() },
+      _ => LkhError::Io(e)
+    }
+  }
+}
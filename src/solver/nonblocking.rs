@@ -0,0 +1,118 @@
+use std::path::Path;
+
+use tokio::{io::{AsyncBufReadExt, AsyncReadExt, BufReader}, process::Command};
+
+use crate::{memory_file::TempFile, parameter::Parameters, report::SolveReport, tour::TourData, LkhError, Node};
+use super::{Solution, Solver};
+
+impl Solver {
+  /// As [`Solver::solve_with`], without blocking the calling thread.
+  ///
+  /// Dropping the returned future kills LKH and removes its temporary files, so it is
+  /// safe to race against other futures (e.g. in `tokio::select!`). The solver's
+  /// [`Solver::timeout`] is honoured and yields [`LkhError::TimedOut`].
+  pub async fn solve_async(
+    &self,
+    problem: impl AsRef<[u8]>,
+    parameters: &Parameters
+  ) -> crate::Result<Solution> {
+    let problem_file = self.temp_file("tsp");
+    tokio::fs::write(problem_file.path(), problem).await?;
+    self.solve_file_async(problem_file.path(), parameters).await
+  }
+
+  /// As [`Solver::solve_file`], without blocking the calling thread.
+  pub async fn solve_file_async(
+    &self,
+    problem_file: &Path,
+    parameters: &Parameters
+  ) -> crate::Result<Solution> {
+    let tour_file = self.temp_file("tour");
+    let parameter_file = self.temp_file("par");
+    tokio::fs::write(
+      parameter_file.path(),
+      self.parameter_contents(problem_file, &tour_file, parameters)?
+    ).await?;
+
+    let mut child =
+      Command::from(self.command(parameter_file.path()))
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| self.spawn_error(e))?;
+
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped")).lines();
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let mut output = String::new();
+    let mut errors = String::new();
+    let mut report = SolveReport::default();
+
+    // the report is fed as LKH prints, so a timeout keeps what was read before it
+    let run = async {
+      let read_stdout = async {
+        while let Some(line) = stdout.next_line().await? {
+          output.push_str(&line);
+          output.push('\n');
+          report.feed(&line);
+        }
+        Ok::<_, std::io::Error>(())
+      };
+      let (read, _) = tokio::join!(read_stdout, stderr.read_to_string(&mut errors));
+      read?;
+      child.wait().await
+    };
+
+    let status = match self.timeout {
+      None => run.await?,
+      Some(t) => {
+        let finished = tokio::time::timeout(t, run).await;
+        match finished {
+          Ok(status) => status?,
+          Err(_) => {
+            // the temporary files are removed on return, once LKH can no longer use them
+            let _ = child.kill().await;
+            let _ = child.wait().await;
+            let partial = read_tour(&tour_file).await.ok().map(|tour| Box::new(Solution { tour, report }));
+            return Err(LkhError::TimedOut { partial })
+          }
+        }
+      }
+    };
+
+    if !status.success() {
+      return Err(LkhError::SolverFailed { status, stdout: output, stderr: errors })
+    }
+
+    let tour = read_tour(&tour_file).await?;
+    Ok(Solution { tour, report })
+  }
+}
+
+async fn read_tour(tour_file: &TempFile) -> crate::Result<Vec<Node>> {
+  let contents = tokio::fs::read_to_string(tour_file.path()).await?;
+  TourData::from_contents(&contents)
+    .parse()
+    .tour()
+}
+
+#[cfg(all(test, unix))]
+mod test {
+  use std::time::{Duration, Instant};
+
+  use crate::solver::running::test::{assert_partial, hanging_lkh};
+  use super::*;
+
+  #[test]
+  fn times_out_with_report_so_far() {
+    let lkh = hanging_lkh();
+    let solver = Solver::with_executable(lkh.path()).timeout(Duration::from_millis(200));
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+
+    let started = Instant::now();
+    match runtime.block_on(solver.solve_async("", &Parameters::default())) {
+      Err(LkhError::TimedOut { partial }) => assert_partial(partial, started),
+      other => panic!("unexpected {other:?}"),
+    }
+  }
+}
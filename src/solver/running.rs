@@ -0,0 +1,270 @@
+use std::{
+  io::{BufRead, BufReader, Lines, Read},
+  process::{Child, ChildStdout, ExitStatus},
+  sync::{mpsc::{self, RecvTimeoutError, Sender}, Arc, Mutex, MutexGuard, PoisonError},
+  thread::JoinHandle,
+  time::{Duration, Instant},
+};
+
+use crate::{memory_file::TempFile, report::{ProgressEvent, SolveReport}, tour::TourData, LkhError};
+use super::Solution;
+
+/// How often [`RunningSolve::finish`] checks whether LKH has exited.
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Why a solve was stopped before LKH finished on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stop {
+  Cancelled,
+  TimedOut,
+}
+
+struct State {
+  child: Child,
+  stop: Option<Stop>,
+  /// The child has been waited on; killing it now would be meaningless
+  exited: bool,
+}
+
+struct Control(Mutex<State>);
+
+impl Control {
+  fn state(&self) -> MutexGuard<'_, State> {
+    self.0.lock().unwrap_or_else(PoisonError::into_inner)
+  }
+
+  fn stop(&self, reason: Stop) {
+    let mut state = self.state();
+    if !state.exited && state.stop.is_none() {
+      state.stop = Some(reason);
+      let _ = state.child.kill();
+    }
+  }
+}
+
+/// Stops a [`RunningSolve`] from any thread. The solve then finishes with
+/// [`LkhError::Cancelled`].
+#[derive(Clone)]
+pub struct CancelHandle(Arc<Control>);
+
+impl CancelHandle {
+  /// Kills LKH. Does nothing if LKH has already exited or was already stopped.
+  pub fn cancel(&self) { self.0.stop(Stop::Cancelled) }
+}
+
+/// An LKH process started by [`super::Solver::spawn`].
+///
+/// Iterating yields a [`ProgressEvent`] for every line LKH prints, as it prints it.
+/// [`RunningSolve::finish`] waits for LKH to exit and reads the tour. Dropping a
+/// `RunningSolve` before it finishes kills LKH and removes its temporary files.
+pub struct RunningSolve {
+  control: Arc<Control>,
+  /// Dropping this releases the deadline watchdog thread, if any
+  watchdog: Option<Sender<()>>,
+  stdout: Lines<BufReader<ChildStdout>>,
+  stderr: Option<JoinHandle<String>>,
+  /// Everything read from stdout so far, kept for [`LkhError::SolverFailed`]
+  output: String,
+  report: SolveReport,
+  tour_file: TempFile,
+  parameter_file: TempFile,
+  problem_file: Option<TempFile>,
+}
+
+impl RunningSolve {
+  pub(crate) fn new(
+    mut child: Child,
+    tour_file: TempFile,
+    parameter_file: TempFile,
+    problem_file: Option<TempFile>,
+  ) -> Self {
+    let stdout = BufReader::new(child.stdout.take().expect("stdout is piped")).lines();
+
+    // stderr is drained on its own thread so a chatty LKH can never block on a full pipe
+    let stderr = child.stderr.take().map(|mut e| {
+      std::thread::spawn(move || {
+        let mut s = String::new();
+        let _ = e.read_to_string(&mut s);
+        s
+      })
+    });
+
+    Self {
+      control: Arc::new(Control(Mutex::new(State { child, stop: None, exited: false }))),
+      watchdog: None,
+      stdout,
+      stderr,
+      output: String::new(),
+      report: SolveReport::default(),
+      tour_file,
+      parameter_file,
+      problem_file,
+    }
+  }
+
+  /// The process id of LKH.
+  pub fn id(&self) -> u32 { self.control.state().child.id() }
+
+  /// What has been reported so far.
+  pub fn report(&self) -> &SolveReport { &self.report }
+
+  pub fn cancel_handle(&self) -> CancelHandle { CancelHandle(self.control.clone()) }
+
+  /// Kills LKH if it is still running at `deadline`. The solve then finishes with
+  /// [`LkhError::TimedOut`]. Replaces any earlier deadline.
+  pub fn deadline(mut self, deadline: Instant) -> Self {
+    let (tx, rx) = mpsc::channel::<()>();
+    let control = self.control.clone();
+
+    std::thread::spawn(move || {
+      let wait = deadline.saturating_duration_since(Instant::now());
+      if let Err(RecvTimeoutError::Timeout) = rx.recv_timeout(wait) {
+        control.stop(Stop::TimedOut);
+      }
+    });
+
+    self.watchdog = Some(tx);
+    self
+  }
+
+  /// As [`RunningSolve::deadline`], measured from now.
+  pub fn timeout(self, timeout: Duration) -> Self {
+    self.deadline(Instant::now() + timeout)
+  }
+
+  fn exit(&mut self, status: ExitStatus) -> crate::Result<()> {
+    let stderr = self.stderr.take().and_then(|h| h.join().ok()).unwrap_or_default();
+    match status.success() {
+      true => Ok(()),
+      false => Err(LkhError::SolverFailed { status, stdout: std::mem::take(&mut self.output), stderr })
+    }
+  }
+
+  fn read_tour(&self) -> crate::Result<Vec<crate::Node>> {
+    TourData::from_file(self.tour_file.path())?
+      .parse()
+      .tour()
+  }
+
+  /// Consumes the remaining output, waits for LKH to exit, and reads the tour it wrote.
+  ///
+  /// If the solve was cancelled or timed out, the error carries the best tour LKH had
+  /// written before it was stopped (LKH writes its tour file after every improving run).
+  pub fn finish(mut self) -> crate::Result<Solution> {
+    for event in &mut self { event?; }
+
+    // polled so the lock is free between polls for cancelling or the watchdog to kill LKH
+    let (status, stop) = loop {
+      {
+        let mut state = self.control.state();
+        if let Some(status) = state.child.try_wait()? {
+          state.exited = true;
+          break (status, state.stop)
+        }
+      }
+      std::thread::sleep(EXIT_POLL_INTERVAL);
+    };
+    self.watchdog = None;
+
+    if let Some(stop) = stop {
+      let partial =
+        self.read_tour()
+          .ok()
+          .map(|tour| Box::new(Solution { tour, report: std::mem::take(&mut self.report) }));
+
+      return Err(match stop {
+        Stop::Cancelled => LkhError::Cancelled { partial },
+        Stop::TimedOut => LkhError::TimedOut { partial },
+      })
+    }
+
+    self.exit(status)?;
+
+    let tour = self.read_tour()?;
+    Ok(Solution { tour, report: std::mem::take(&mut self.report) })
+  }
+}
+
+impl Iterator for RunningSolve {
+  type Item = crate::Result<ProgressEvent>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let line = match self.stdout.next()? {
+      Ok(line) => line,
+      Err(e) => return Some(Err(e.into()))
+    };
+
+    self.output.push_str(&line);
+    self.output.push('\n');
+    self.report.feed(&line);
+
+    Some(Ok(ProgressEvent::parse(&line)))
+  }
+}
+
+impl Drop for RunningSolve {
+  fn drop(&mut self) {
+    // the temporary files are removed after this, once LKH can no longer write to them
+    let mut state = self.control.state();
+    if !state.exited {
+      let _ = state.child.kill();
+      let _ = state.child.wait();
+      state.exited = true;
+    }
+  }
+}
+
+#[cfg(all(test, unix))]
+pub(crate) mod test {
+  use std::os::unix::fs::PermissionsExt;
+
+  use crate::{parameter::Parameters, Solver};
+  use super::*;
+
+  /// An executable standing in for LKH: it writes a tour and reports a run, then closes
+  /// its output and keeps running until it is killed.
+  pub(crate) fn hanging_lkh() -> TempFile {
+    let script = TempFile::new(&std::env::temp_dir(), "sh");
+    std::fs::write(script.path(), "\
+#!/bin/sh
+tour=$(sed -n 's/^TOUR_FILE = //p' \"$1\")
+printf 'TYPE : TOUR\\nDIMENSION : 3\\nTOUR_SECTION\\n1\\n2\\n3\\n-1\\nEOF\\n' > \"$tour\"
+echo 'Run 1: Cost = 5, Time = 0.00 sec.'
+exec sleep 30 >/dev/null 2>&1
+").unwrap();
+    std::fs::set_permissions(script.path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+    script
+  }
+
+  pub(crate) fn assert_partial(partial: Option<Box<Solution>>, started: Instant) {
+    assert!(started.elapsed() < Duration::from_secs(10));
+    let partial = partial.expect("the tour written before stopping");
+    assert_eq!(partial.tour, vec![0, 1, 2]);
+    assert_eq!(partial.report.runs.len(), 1);
+  }
+
+  #[test]
+  fn cancels_while_finishing() {
+    let lkh = hanging_lkh();
+    let running = Solver::with_executable(lkh.path()).spawn("", &Parameters::default()).unwrap();
+    let cancel = running.cancel_handle();
+    let started = Instant::now();
+
+    std::thread::spawn(move || { std::thread::sleep(Duration::from_millis(200)); cancel.cancel() });
+    match running.finish() {
+      Err(LkhError::Cancelled { partial }) => assert_partial(partial, started),
+      other => panic!("unexpected {other:?}"),
+    }
+  }
+
+  #[test]
+  fn times_out_while_finishing() {
+    let lkh = hanging_lkh();
+    let started = Instant::now();
+    let solved = Solver::with_executable(lkh.path()).timeout(Duration::from_millis(200)).solve("");
+    match solved {
+      Err(LkhError::TimedOut { partial }) => assert_partial(partial, started),
+      other => panic!("unexpected {other:?}"),
+    }
+  }
+}
@@ -1,10 +1,10 @@
 use std::{fs::OpenOptions, io::{BufReader, Read}, path::PathBuf, str::{FromStr, Lines}};
-use crate::util::string::{string, PoolString};
+use crate::{util::string::{string, PoolString}, LkhError};
 
 pub struct TourData<R> { reader: R }
 
 impl TourData<PoolString> {
-  pub fn from_file(file: &PathBuf) -> std::io::Result<Self> {
+  pub fn from_file(file: &PathBuf) -> crate::Result<Self> {
     let reader = OpenOptions::new().read(true).open(file)?;
     let reader = {
       let mut s = string();
@@ -17,58 +17,136 @@ impl TourData<PoolString> {
     })
   }
 
-  pub fn parse(&self) -> TourParse { TourParse::from(self) }
+  pub fn from_contents(contents: &str) -> Self {
+    let mut reader = string();
+    reader.push_str(contents);
+    Self { reader }
+  }
+
+  pub fn parse(&self) -> TourParse<'_> { TourParse::from(self) }
 }
 
-pub struct TourParse<'a>(Lines<'a>);
+pub struct TourParse<'a> {
+  lines: Lines<'a>,
+  /// 1-indexed number of the line most recently taken from `lines`
+  line: usize,
+}
 
 impl<'a> From<&'a TourData<PoolString>> for TourParse<'a> {
   fn from(data: &'a TourData<PoolString>) -> Self {
-    Self(data.reader.lines())
+    Self { lines: data.reader.lines(), line: 0 }
   }
 }
 
 impl<'a> TourParse<'a> {
-  fn parse<'b>(&'b mut self, id: &'b impl AsRef<str>) -> std::io::Result<&'b str> {
-    while let Some(line) = self.0.next() {
+  fn next_line(&mut self) -> Option<&'a str> {
+    let line = self.lines.next()?;
+    self.line += 1;
+    Some(line)
+  }
+
+  fn parse(&mut self, id: &impl AsRef<str>) -> crate::Result<&'a str> {
+    while let Some(line) = self.next_line() {
       if line.starts_with(id.as_ref()) {
         if let Some(location) = line.find(':').or_else(|| line.find('=')) {
-          return Ok(&line[location..].trim())
+          return Ok(line[location + 1..].trim())
         } else {
-          return Ok(&line.trim())
+          return Ok(line.trim())
         }
       }
     }
 
-    Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "could not find keyword"))    
+    Err(LkhError::malformed_tour(self.line, format!("could not find keyword {}", id.as_ref())))
   }
 
-  fn parse_u32(n: &str, s: &str) -> std::io::Result<u32> {
+  fn parse_u32(&self, n: &str, s: &str) -> crate::Result<u32> {
     u32::from_str(s)
-      .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{n} is not a number")))
+      .map_err(|_| LkhError::malformed_tour(self.line, format!("{n} is not a number: {s:?}")))
   }
 
-  pub fn dimension(&mut self) -> std::io::Result<u32> {
-    self
-      .parse(&"DIMENSION")
-      .and_then(|s| Self::parse_u32("DIMENSION", s))
+  pub fn dimension(&mut self) -> crate::Result<u32> {
+    let s = self.parse(&"DIMENSION")?;
+    self.parse_u32("DIMENSION", s)
   }
 
-  pub fn tour(&mut self) -> std::io::Result<Vec<u32>> {
+  pub fn tour(&mut self) -> crate::Result<Vec<u32>> {
     let mut buffer = vec![];
 
     self.parse(&"TOUR_SECTION")?;
 
-    (&mut self.0)
-      .map(|s| s.trim())
-      .map_while(|s| {
-        if s != "-1" { Some(s) } else { None } })
-      .try_for_each(|entry| {
-        let r = Self::parse_u32("TOUR_SECTION", entry)?;
-        buffer.push(r - 1);
-        std::io::Result::<()>::Ok(())
-      })?;
+    loop {
+      let Some(entry) = self.next_line().map(str::trim) else {
+        return Err(LkhError::malformed_tour(self.line, "TOUR_SECTION is not terminated by -1"))
+      };
+      if entry == "-1" || entry == "EOF" { break }
+
+      let r = self.parse_u32("TOUR_SECTION", entry)?;
+      if r == 0 {
+        return Err(LkhError::malformed_tour(self.line, "TOUR_SECTION entries are 1-indexed"))
+      }
+      buffer.push(r - 1);
+    }
 
     Ok(buffer)
   }
 }
+
+/// Reads the routes of an LKH-3 `MTSP_SOLUTION_FILE`, one per salesman, each starting and
+/// ending at the depot. Nodes are 0-indexed.
+pub fn parse_mtsp_solution(contents: &str) -> crate::Result<Vec<Vec<u32>>> {
+  let mut lines = contents.lines().enumerate().map(|(i, l)| (i + 1, l.trim()));
+
+  // "<name>, Cost: <penalty>_<cost>" then "The tours traveled by the <m> salesmen are:"
+  if !lines.any(|(_, l)| l.ends_with("salesmen are:")) {
+    return Err(LkhError::malformed_solution("no routes in the mTSP solution"))
+  }
+
+  lines
+    .filter(|(_, l)| !l.is_empty())
+    .map(|(line, l)| {
+      // "1 14 25 ... 1  (#12)  Cost: 100"
+      l.split_whitespace()
+        .take_while(|t| !t.starts_with('('))
+        .map(|t| match u32::from_str(t) {
+          Ok(0) | Err(_) => Err(LkhError::malformed_tour(line, format!("not a node: {t:?}"))),
+          Ok(v) => Ok(v - 1),
+        })
+        .collect()
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn parses_tour() {
+    let d = TourData::from_contents("NAME : t\nTYPE : TOUR\nDIMENSION : 3\nTOUR_SECTION\n1\n3\n2\n-1\nEOF\n");
+    let mut p = d.parse();
+    assert_eq!(p.dimension().unwrap(), 3);
+    assert_eq!(p.tour().unwrap(), vec![0, 2, 1]);
+  }
+
+  #[test]
+  fn parses_mtsp_solution() {
+    let routes = parse_mtsp_solution(
+      "eil7-m2, Cost: 0_30\nThe tours traveled by the 2 salesmen are:\n1 3 2 1  (#2)  Cost: 12\n1 4 5 6 7 1  (#4)  Cost: 18\n"
+    ).unwrap();
+    assert_eq!(routes, vec![vec![0, 2, 1, 0], vec![0, 3, 4, 5, 6, 0]]);
+    assert!(matches!(parse_mtsp_solution("x, Cost: 0_1\n"), Err(LkhError::MalformedTour { line: None, .. })));
+    assert!(matches!(
+      parse_mtsp_solution("The tours traveled by the 1 salesmen are:\n1 x 1\n"),
+      Err(LkhError::MalformedTour { line: Some(2), .. })
+    ));
+  }
+
+  #[test]
+  fn reports_line_of_bad_entry() {
+    let d = TourData::from_contents("TOUR_SECTION\n1\nx\n-1\n");
+    match d.parse().tour() {
+      Err(LkhError::MalformedTour { line, .. }) => assert_eq!(line, Some(3)),
+      other => panic!("unexpected {:?}", other.map(|_| ())),
+    }
+  }
+}
@@ -0,0 +1,155 @@
+use crate::{header::{EdgeWeightFormat, EdgeWeightKind, ProblemKind}, matrix::Matrix, parameter::Parameters, report::SolveReport, LkhError, Node, Problem, Solver};
+
+/// How an asymmetric problem is handed to LKH.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AtspTransform {
+  /// Written as `TYPE: ATSP`; LKH transforms it internally.
+  #[default]
+  Native,
+  /// Transformed here into a symmetric TSP on `2n` nodes (Jonker & Volgenant): every node
+  /// gets a ghost joined to it by a fixed edge, and the arc `i -> j` becomes the edge
+  /// between `i` and the ghost of `j`.
+  Symmetric,
+}
+
+/// A directed tour.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtspSolution {
+  /// Every node once, in the direction of travel
+  pub tour: Vec<Node>,
+  /// The cost of the closed tour under the input matrix
+  pub cost: i64,
+  pub report: SolveReport,
+}
+
+impl Solver {
+  /// Solves the asymmetric TSP where `costs.get(i, j)` is the cost of travelling from
+  /// `i` to `j`. The returned tour is oriented in whichever direction is cheaper.
+  pub fn solve_atsp(
+    &self,
+    costs: &Matrix,
+    transform: AtspTransform,
+    parameters: &Parameters
+  ) -> crate::Result<AtspSolution> {
+    let n = costs.dimension();
+    if n < 3 {
+      // LKH refuses these, and there is only one tour anyway
+      let tour = (0..n as Node).collect::<Vec<_>>();
+      return Ok(AtspSolution { cost: costs.tour_cost(&tour), tour, report: SolveReport::default() })
+    }
+
+    let solution = match transform {
+      AtspTransform::Native => {
+        let problem =
+          Problem::with(Vec::with_capacity(4096))
+            .header()
+              .problem_kind(ProblemKind::ATSP)?
+              .dimension(n as u32)?
+              .edge_weight_kind(EdgeWeightKind::EXPLICIT)?
+              .edge_weight_format(EdgeWeightFormat::FULL_MATRIX)?
+            .coords()
+            .edge_weights()
+              .begin_edge_weights()?
+              .write_matrix(EdgeWeightFormat::FULL_MATRIX, costs)?
+            .finish();
+        self.solve_with(problem, parameters)?
+      },
+      AtspTransform::Symmetric => {
+        let forbidden = forbidden_weight(costs)?;
+        let problem =
+          Problem::with(Vec::with_capacity(4096))
+            .header()
+              .problem_kind(ProblemKind::TSP)?
+              .dimension(2 * n as u32)?
+              .edge_weight_kind(EdgeWeightKind::EXPLICIT)?
+              .edge_weight_format(EdgeWeightFormat::UPPER_ROW)?
+            .coords()
+            .edge_weights()
+              .begin_edge_weights()?
+              .write_with(EdgeWeightFormat::UPPER_ROW, 2 * n, |i, j| symmetric_weight(costs, forbidden, i, j))?
+            .fixed_edges()
+              .begin_fixed_edges()?
+              .write_fixed_node_pairs((0..n as Node).map(|i| [i, i + n as Node]))?
+              .end_fixed_edges()?
+            .finish();
+        let mut solution = self.solve_with(problem, parameters)?;
+        solution.tour.retain(|v| (*v as usize) < n);
+        solution
+      },
+    };
+
+    let (tour, cost) = orient(costs, solution.tour);
+    Ok(AtspSolution { tour, cost, report: solution.report })
+  }
+}
+
+/// The largest weight the symmetric transform writes, far enough from overflowing LKH's
+/// `int` weights once they are scaled by its `PRECISION`.
+const MAX_FORBIDDEN_WEIGHT: i64 = i32::MAX as i64 / 1000;
+
+/// Larger than any tour's cost, so LKH never uses a forbidden edge. Fails when that
+/// exceeds [`MAX_FORBIDDEN_WEIGHT`]; such costs need rescaling or [`AtspTransform::Native`].
+fn forbidden_weight(costs: &Matrix) -> crate::Result<i64> {
+  let max = costs.as_slice().iter().map(|w| w.abs()).max().unwrap_or(0);
+  let forbidden = max.saturating_add(1).saturating_mul(costs.dimension() as i64);
+  if forbidden > MAX_FORBIDDEN_WEIGHT {
+    return Err(LkhError::invalid_problem(format!(
+      "costs up to {max} over {} nodes are too large for the symmetric transform, whose weights are limited to {MAX_FORBIDDEN_WEIGHT}",
+      costs.dimension()
+    )))
+  }
+  Ok(forbidden)
+}
+
+/// The weight between node `i < j` of the `2n` node symmetric problem, where node `n + v`
+/// is the ghost of `v`.
+fn symmetric_weight(costs: &Matrix, forbidden: i64, i: usize, j: usize) -> i64 {
+  let n = costs.dimension();
+  match (i < n, j < n) {
+    (true, false) if j - n == i => 0,
+    (true, false) => costs.get(i, j - n),
+    _ => forbidden,
+  }
+}
+
+/// Keeps the first node in place and reverses the rest when travelling the other way is cheaper.
+fn orient(costs: &Matrix, mut tour: Vec<Node>) -> (Vec<Node>, i64) {
+  let forward = costs.tour_cost(&tour);
+  if tour.len() > 2 { tour[1..].reverse(); }
+  let backward = costs.tour_cost(&tour);
+  if backward < forward { return (tour, backward) }
+  if tour.len() > 2 { tour[1..].reverse(); }
+  (tour, forward)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn orients_tours_cheapest_first() {
+    // going 0 -> 1 -> 2 -> 3 costs 4, the other way 40
+    let costs = Matrix::from_fn(4, |i, j| if (i + 1) % 4 == j { 1 } else { 10 });
+    assert_eq!(orient(&costs, vec![0, 3, 2, 1]), (vec![0, 1, 2, 3], 4));
+    assert_eq!(orient(&costs, vec![2, 3, 0, 1]), (vec![2, 3, 0, 1], 4));
+  }
+
+  #[test]
+  fn transforms_arcs_to_ghost_edges() {
+    let costs = Matrix::from_rows(&[[0, 1, 2], [3, 0, 4], [5, 6, 0]]).unwrap();
+    let forbidden = forbidden_weight(&costs).unwrap();
+    assert_eq!(symmetric_weight(&costs, forbidden, 0, 3), 0);
+    assert_eq!(symmetric_weight(&costs, forbidden, 1, 3), 3);
+    assert_eq!(symmetric_weight(&costs, forbidden, 2, 4), 6);
+    assert_eq!(symmetric_weight(&costs, forbidden, 0, 1), forbidden);
+    assert_eq!(symmetric_weight(&costs, forbidden, 3, 4), forbidden);
+    assert!(forbidden > 3 * 6);
+  }
+
+  #[test]
+  fn rejects_costs_beyond_forbidden_weight() {
+    let costs = Matrix::from_fn(4, |i, j| if i == j { 0 } else { 1_000_000 });
+    assert!(matches!(forbidden_weight(&costs), Err(LkhError::InvalidProblem(_))));
+    assert!(Solver::with_executable("lkh").solve_atsp(&costs, AtspTransform::Symmetric, &Parameters::default()).is_err());
+  }
+}
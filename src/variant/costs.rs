@@ -0,0 +1,78 @@
+use std::io::Write;
+
+use crate::{header::{EdgeWeightFormat, EdgeWeightKind, Header}, matrix::Matrix, node_data::NodeData, Node};
+
+/// Where the travel cost between two nodes comes from, both for LKH and for the lengths
+/// computed when decoding its tours.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Costs {
+  /// `EUC_2D`: the Euclidean distance between points, rounded to the nearest integer as
+  /// LKH does
+  Euclidean(Vec<[f32; 2]>),
+  /// `EXPLICIT`: written as a `FULL_MATRIX`
+  Explicit(Matrix),
+}
+
+impl Costs {
+  pub fn dimension(&self) -> usize {
+    match self {
+      Self::Euclidean(points) => points.len(),
+      Self::Explicit(m) => m.dimension(),
+    }
+  }
+
+  pub fn cost(&self, i: Node, j: Node) -> i64 {
+    match self {
+      Self::Euclidean(points) => {
+        let ([xi, yi], [xj, yj]) = (points[i as usize], points[j as usize]);
+        let (dx, dy) = (xi as f64 - xj as f64, yi as f64 - yj as f64);
+        ((dx * dx + dy * dy).sqrt() + 0.5) as i64
+      },
+      Self::Explicit(m) => m.get(i as usize, j as usize),
+    }
+  }
+
+  /// The cost of visiting `nodes` in order without returning to the first.
+  pub fn path_cost(&self, nodes: &[Node]) -> i64 {
+    nodes.windows(2).map(|w| self.cost(w[0], w[1])).sum()
+  }
+
+  /// The cost of the closed tour through `nodes`, back to the first.
+  pub fn tour_cost(&self, nodes: &[Node]) -> i64 {
+    match (nodes.first(), nodes.last()) {
+      (Some(&first), Some(&last)) => self.path_cost(nodes) + self.cost(last, first),
+      _ => 0,
+    }
+  }
+
+  /// Writes the `EDGE_WEIGHT_*` keywords, then the coordinates or weights, leaving the
+  /// writer ready for the node sections of the problem.
+  pub(crate) fn write<W: Write>(&self, header: Header<W>) -> crate::Result<NodeData<W>> {
+    Ok(match self {
+      Self::Euclidean(points) =>
+        header
+          .edge_weight_kind(EdgeWeightKind::EUC_2D)?
+          .coords()
+            .begin_node_coordinates()?
+            .write_coordinates(points.iter().copied())?
+          .node_data(),
+      Self::Explicit(m) =>
+        header
+          .edge_weight_kind(EdgeWeightKind::EXPLICIT)?
+          .edge_weight_format(EdgeWeightFormat::FULL_MATRIX)?
+          .coords()
+          .edge_weights()
+            .begin_edge_weights()?
+            .write_matrix(EdgeWeightFormat::FULL_MATRIX, m)?
+          .node_data(),
+    })
+  }
+}
+
+impl From<Matrix> for Costs {
+  fn from(m: Matrix) -> Self { Self::Explicit(m) }
+}
+
+impl From<Vec<[f32; 2]>> for Costs {
+  fn from(points: Vec<[f32; 2]>) -> Self { Self::Euclidean(points) }
+}
@@ -0,0 +1,132 @@
+use crate::{header::ProblemKind, parameter::Parameters, report::SolveReport, LkhError, Node, Problem, Solution, Solver};
+use super::{check_tour_nodes, gtsp::cluster_membership, Costs};
+
+/// A clustered TSP: the nodes are partitioned into clusters, and the tour visits all nodes
+/// of a cluster before moving to the next.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ctsp {
+  costs: Costs,
+  clusters: Vec<Vec<Node>>,
+}
+
+/// The nodes of one cluster, in the order they are visited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterVisit {
+  /// Index into the problem's clusters
+  pub cluster: usize,
+  pub nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CtspSolution {
+  /// The tour, starting at the first node of a cluster
+  pub tour: Vec<Node>,
+  /// The tour split into its clusters, in visiting order
+  pub clusters: Vec<ClusterVisit>,
+  /// The cost of the closed tour
+  pub cost: i64,
+  pub report: SolveReport,
+}
+
+impl Ctsp {
+  /// `clusters` must put every node in exactly one cluster.
+  pub fn new(costs: impl Into<Costs>, clusters: Vec<Vec<Node>>) -> Self {
+    Self { costs: costs.into(), clusters }
+  }
+
+  pub fn validate(&self) -> crate::Result<()> {
+    if self.costs.dimension() < 3 {
+      return Err(LkhError::invalid_problem(format!("a CTSP needs at least 3 nodes, not {}", self.costs.dimension())))
+    }
+    cluster_membership(self.costs.dimension(), &self.clusters).map(|_| ())
+  }
+
+  pub fn to_tsplib(&self) -> crate::Result<Vec<u8>> {
+    self.validate()?;
+    let h =
+      Problem::with(Vec::with_capacity(4096))
+        .header()
+          .problem_kind(ProblemKind::CTSP)?
+          .dimension(self.costs.dimension() as u32)?
+          .keyword("GTSP_SETS", self.clusters.len())?;
+    Ok(
+      self.costs.write(h)?
+        .begin_ctsp_sets()?
+        .write_sets(self.clusters.iter())?
+        .finish()
+    )
+  }
+
+  /// Groups a tour from LKH by cluster, failing when a cluster is not visited contiguously.
+  pub fn decode(&self, mut tour: Vec<Node>, report: SolveReport) -> crate::Result<CtspSolution> {
+    let cluster_of = cluster_membership(self.costs.dimension(), &self.clusters)?;
+    check_tour_nodes(&tour, self.costs.dimension())?;
+    let cluster = |v: &Node| cluster_of[*v as usize];
+
+    // start where a cluster starts, so that none wraps around the end
+    if let Some(start) = (0..tour.len()).find(|i| cluster(&tour[*i]) != cluster(&tour[(i + tour.len() - 1) % tour.len()])) {
+      tour.rotate_left(start);
+    }
+
+    let mut clusters: Vec<ClusterVisit> = vec![];
+    for &v in &tour {
+      match clusters.last_mut() {
+        Some(last) if last.cluster == cluster(&v) => last.nodes.push(v),
+        _ => clusters.push(ClusterVisit { cluster: cluster(&v), nodes: vec![v] }),
+      }
+    }
+
+    let mut seen = vec![false; self.clusters.len()];
+    if let Some(c) = clusters.iter().find(|c| std::mem::replace(&mut seen[c.cluster], true)) {
+      let message = format!("cluster {} is entered more than once", c.cluster);
+      return Err(LkhError::Infeasible { message, solution: Box::new(Solution { tour, report }) })
+    }
+
+    Ok(CtspSolution { cost: self.costs.tour_cost(&tour), tour, clusters, report })
+  }
+}
+
+impl Solver {
+  /// Solves a CTSP; LKH-3 only.
+  pub fn solve_ctsp(
+    &self,
+    problem: &Ctsp,
+    parameters: &Parameters
+  ) -> crate::Result<CtspSolution> {
+    let solution = self.solve_with(problem.to_tsplib()?, parameters)?;
+    problem.decode(solution.tour, solution.report)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{header::EdgeWeightKind, instance::ProblemInstance, variant::test::{lines, read_back}};
+  use super::*;
+
+  #[test]
+  fn groups_tour_by_cluster() {
+    let p = Ctsp::new(vec![[0., 0.], [1., 0.], [5., 0.], [6., 0.], [7., 0.]], vec![vec![0, 1], vec![2, 3, 4]]);
+    assert_eq!(
+      read_back(p.to_tsplib()),
+      ProblemInstance::new(ProblemKind::CTSP, 5)
+        .keyword("GTSP_SETS", 2)
+        .coordinates_2d(EdgeWeightKind::EUC_2D, vec![[0., 0.], [1., 0.], [5., 0.], [6., 0.], [7., 0.]])
+        .section("CTSP_SET_SECTION", lines(&["1 1 2 -1", "2 3 4 5 -1"]))
+    );
+
+    let s = p.decode(vec![3, 4, 1, 0, 2], SolveReport::default()).unwrap();
+    assert_eq!(s.tour, vec![1, 0, 2, 3, 4]);
+    assert_eq!(s.clusters, vec![
+      ClusterVisit { cluster: 0, nodes: vec![1, 0] },
+      ClusterVisit { cluster: 1, nodes: vec![2, 3, 4] },
+    ]);
+    assert_eq!(s.cost, 1 + 5 + 1 + 1 + 6);
+  }
+
+  #[test]
+  fn rejects_split_clusters() {
+    let p = Ctsp::new(vec![[0., 0.], [1., 0.], [5., 0.], [6., 0.], [7., 0.]], vec![vec![0, 1], vec![2, 3, 4]]);
+    assert!(matches!(p.decode(vec![0, 2, 1, 3, 4], SolveReport::default()), Err(LkhError::Infeasible { .. })));
+    assert!(matches!(p.decode(vec![0, 1, 2, 3, 9], SolveReport::default()), Err(LkhError::MalformedTour { line: None, .. })));
+  }
+}
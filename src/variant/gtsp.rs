@@ -0,0 +1,146 @@
+use crate::{header::ProblemKind, parameter::Parameters, report::SolveReport, LkhError, Node, Problem, Solution, Solver};
+use super::{check_tour_nodes, Costs};
+
+/// A generalized TSP: the nodes are partitioned into clusters, and the tour visits exactly
+/// one node of every cluster.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gtsp {
+  costs: Costs,
+  clusters: Vec<Vec<Node>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GtspSolution {
+  /// The visited nodes, in order
+  pub tour: Vec<Node>,
+  /// The clusters (indices into the problem's clusters) in the order they are visited
+  pub cluster_order: Vec<usize>,
+  /// The node visited in each cluster, indexed like the problem's clusters
+  pub chosen: Vec<Node>,
+  /// The cost of the closed tour
+  pub cost: i64,
+  pub report: SolveReport,
+}
+
+impl Gtsp {
+  /// `clusters` must put every node in exactly one cluster.
+  pub fn new(costs: impl Into<Costs>, clusters: Vec<Vec<Node>>) -> Self {
+    Self { costs: costs.into(), clusters }
+  }
+
+  pub fn clusters(&self) -> &[Vec<Node>] { &self.clusters }
+
+  pub fn validate(&self) -> crate::Result<()> {
+    self.cluster_of().map(|_| ())
+  }
+
+  /// The cluster of every node, checking that the clusters partition the nodes.
+  fn cluster_of(&self) -> crate::Result<Vec<usize>> {
+    cluster_membership(self.costs.dimension(), &self.clusters)
+  }
+
+  pub fn to_tsplib(&self) -> crate::Result<Vec<u8>> {
+    self.validate()?;
+    let kind = match &self.costs {
+      Costs::Explicit(m) if !m.is_symmetric() => ProblemKind::AGTSP,
+      _ => ProblemKind::GTSP,
+    };
+    let h =
+      Problem::with(Vec::with_capacity(4096))
+        .header()
+          .problem_kind(kind)?
+          .dimension(self.costs.dimension() as u32)?
+          .keyword("GTSP_SETS", self.clusters.len())?;
+    Ok(
+      self.costs.write(h)?
+        .begin_gtsp_sets()?
+        .write_sets(self.clusters.iter())?
+        .finish()
+    )
+  }
+
+  /// Reads which node LKH chose in each cluster, failing when a cluster is visited more
+  /// than once or not at all.
+  pub fn decode(&self, tour: Vec<Node>, report: SolveReport) -> crate::Result<GtspSolution> {
+    let cluster_of = self.cluster_of()?;
+    check_tour_nodes(&tour, self.costs.dimension())?;
+    let mut chosen = vec![None; self.clusters.len()];
+    let mut cluster_order = Vec::with_capacity(self.clusters.len());
+
+    for &v in &tour {
+      let c = cluster_of[v as usize];
+      if let Some(previous) = chosen[c].replace(v) {
+        let message = format!("cluster {c} is visited at nodes {previous} and {v}");
+        return Err(LkhError::Infeasible { message, solution: Box::new(Solution { tour, report }) })
+      }
+      cluster_order.push(c);
+    }
+    let Some(chosen) = chosen.into_iter().collect::<Option<Vec<_>>>() else {
+      let message = "a cluster is not visited".to_owned();
+      return Err(LkhError::Infeasible { message, solution: Box::new(Solution { tour, report }) })
+    };
+
+    Ok(GtspSolution { cost: self.costs.tour_cost(&tour), tour, cluster_order, chosen, report })
+  }
+}
+
+/// The cluster of each of `dimension` nodes, failing unless `clusters` partitions them.
+pub(crate) fn cluster_membership(dimension: usize, clusters: &[Vec<Node>]) -> crate::Result<Vec<usize>> {
+  let fail = |m: String| Err(LkhError::invalid_problem(m));
+  let mut cluster_of = vec![usize::MAX; dimension];
+
+  for (c, cluster) in clusters.iter().enumerate() {
+    if cluster.is_empty() { return fail(format!("cluster {c} is empty")) }
+    for &v in cluster {
+      match cluster_of.get_mut(v as usize) {
+        None => return fail(format!("node {v} of cluster {c} is not one of the {dimension} nodes")),
+        Some(slot) if *slot != usize::MAX => return fail(format!("node {v} is in clusters {} and {c}", *slot)),
+        Some(slot) => *slot = c,
+      }
+    }
+  }
+  match cluster_of.iter().position(|c| *c == usize::MAX) {
+    Some(v) => fail(format!("node {v} is in no cluster")),
+    None => Ok(cluster_of),
+  }
+}
+
+impl Solver {
+  /// Solves a GTSP; LKH-3 only.
+  pub fn solve_gtsp(
+    &self,
+    problem: &Gtsp,
+    parameters: &Parameters
+  ) -> crate::Result<GtspSolution> {
+    let solution = self.solve_with(problem.to_tsplib()?, parameters)?;
+    problem.decode(solution.tour, solution.report)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{header::EdgeWeightKind, instance::ProblemInstance, variant::test::{lines, read_back}};
+  use super::*;
+
+  #[test]
+  fn writes_sets() {
+    let p = Gtsp::new(vec![[0., 0.], [5., 0.], [0., 3.], [4., 3.], [9., 9.]], vec![vec![0, 1], vec![2, 3, 4]]);
+    assert_eq!(
+      read_back(p.to_tsplib()),
+      ProblemInstance::new(ProblemKind::GTSP, 5)
+        .keyword("GTSP_SETS", 2)
+        .coordinates_2d(EdgeWeightKind::EUC_2D, vec![[0., 0.], [5., 0.], [0., 3.], [4., 3.], [9., 9.]])
+        .section("GTSP_SET_SECTION", lines(&["1 1 2 -1", "2 3 4 5 -1"]))
+    );
+  }
+
+  #[test]
+  fn decodes_chosen_nodes() {
+    let p = Gtsp::new(vec![[0., 0.], [5., 0.], [0., 3.], [4., 3.], [9., 9.]], vec![vec![0, 1], vec![2, 3, 4]]);
+    let s = p.decode(vec![3, 0], SolveReport::default()).unwrap();
+    assert_eq!((s.cluster_order, s.chosen, s.cost), (vec![1, 0], vec![0, 3], 10));
+    assert!(matches!(p.decode(vec![2, 0, 3], SolveReport::default()), Err(LkhError::Infeasible { .. })));
+    assert!(Gtsp::new(vec![[0., 0.]; 3], vec![vec![0, 1]]).validate().is_err());
+    assert!(matches!(p.decode(vec![0, 5], SolveReport::default()), Err(LkhError::MalformedTour { line: None, .. })));
+  }
+}
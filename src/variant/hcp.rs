@@ -0,0 +1,80 @@
+use std::collections::HashSet;
+
+use crate::{header::{EdgeFormat, ProblemKind}, parameter::Parameters, Edge, Node, Problem, Solver};
+
+impl Solver {
+  /// Looks for a cycle through every node of the undirected graph `edges`, whose nodes are
+  /// `0..=` the largest node mentioned. LKH reports a cost of 0 when it finds one; the
+  /// cycle is returned only after checking that each consecutive pair is one of `edges`.
+  pub fn find_hamiltonian_cycle(
+    &self,
+    edges: &[Edge],
+    parameters: &Parameters
+  ) -> crate::Result<Option<Vec<Node>>> {
+    let edges = normalize(edges);
+    let dimension = edges.iter().flatten().max().map_or(0, |v| v + 1);
+    if dimension < 3 { return Ok(None) }
+
+    let problem =
+      Problem::with(Vec::with_capacity(4096))
+        .header()
+          .problem_kind(ProblemKind::HCP)?
+          .dimension(dimension)?
+          .edge_data_format(EdgeFormat::EDGE_LIST)?
+        .coords()
+        .edges()
+          .begin_adjacency()?
+          .write_edge_list(edges.iter().copied())?
+          .end_edge_data()?
+        .finish();
+
+    let solution = self.solve_with(problem, parameters)?;
+    let found = solution.report.best_cost().is_none_or(|c| c.value == 0 && c.is_feasible());
+    Ok(match found && is_cycle_of(&edges, dimension, &solution.tour) {
+      true => Some(solution.tour),
+      false => None,
+    })
+  }
+}
+
+/// Each edge once as `[low, high]`, without loops.
+fn normalize(edges: &[Edge]) -> Vec<Edge> {
+  let mut edges = edges
+    .iter()
+    .filter(|[a, b]| a != b)
+    .map(|&[a, b]| [a.min(b), a.max(b)])
+    .collect::<Vec<_>>();
+  edges.sort_unstable();
+  edges.dedup();
+  edges
+}
+
+/// Whether `tour` visits each of the `dimension` nodes once, moving only along `edges`.
+fn is_cycle_of(edges: &[Edge], dimension: u32, tour: &[Node]) -> bool {
+  let edges = edges.iter().copied().collect::<HashSet<_>>();
+  let mut seen = vec![false; dimension as usize];
+  let visits_all = tour.len() == dimension as usize
+    && tour.iter().all(|v| seen.get_mut(*v as usize).is_some_and(|s| !std::mem::replace(s, true)));
+
+  visits_all && (0..tour.len()).all(|i| {
+    let (a, b) = (tour[i], tour[(i + 1) % tour.len()]);
+    edges.contains(&[a.min(b), a.max(b)])
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn verifies_cycles_against_edges() {
+    let edges = normalize(&[[1, 0], [1, 2], [2, 3], [3, 0], [0, 2], [0, 1], [3, 3]]);
+    assert_eq!(edges, vec![[0, 1], [0, 2], [0, 3], [1, 2], [2, 3]]);
+
+    assert!(is_cycle_of(&edges, 4, &[0, 1, 2, 3]));
+    assert!(is_cycle_of(&edges, 4, &[3, 2, 1, 0]));
+    assert!(!is_cycle_of(&edges, 4, &[0, 2, 1, 3]));
+    assert!(!is_cycle_of(&edges, 4, &[0, 1, 2]));
+    assert!(!is_cycle_of(&edges, 4, &[0, 1, 2, 2]));
+  }
+}
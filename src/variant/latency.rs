@@ -0,0 +1,135 @@
+use crate::{header::ProblemKind, parameter::Parameters, report::SolveReport, LkhError, Node, Problem, Solver};
+use super::{check_tour_nodes, Costs};
+
+/// Which LKH-3 problem type a [`MinimumLatency`] is written as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[allow(non_camel_case_types)]
+pub enum LatencyKind {
+  /// The traveling repairman problem
+  #[default]
+  TRP,
+  /// The minimum latency problem
+  MLP,
+}
+
+/// A tour from the depot minimizing the summed time until each node is reached, rather than
+/// its length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinimumLatency {
+  costs: Costs,
+  depot: Node,
+  kind: LatencyKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencySolution {
+  /// Every node once, starting at the depot
+  pub tour: Vec<Node>,
+  /// The time each node is reached, indexed by node; 0 for the depot
+  pub latencies: Vec<i64>,
+  /// The summed latency of every node but the depot, to compare with the cost LKH reports
+  pub total_latency: i64,
+  /// When the tour is back at the depot
+  pub return_time: i64,
+  pub report: SolveReport,
+}
+
+impl MinimumLatency {
+  pub fn new(costs: impl Into<Costs>) -> Self {
+    Self { costs: costs.into(), depot: 0, kind: LatencyKind::default() }
+  }
+
+  /// The node the tour starts at; node 0 unless set.
+  pub fn depot(mut self, depot: Node) -> Self { self.depot = depot; self }
+
+  pub fn kind(mut self, kind: LatencyKind) -> Self { self.kind = kind; self }
+
+  pub fn validate(&self) -> crate::Result<()> {
+    let n = self.costs.dimension();
+    if n < 3 {
+      return Err(LkhError::invalid_problem(format!("a {:?} needs at least 3 nodes, not {n}", self.kind)))
+    }
+    if self.depot as usize >= n {
+      return Err(LkhError::invalid_problem(format!("depot {} is not one of the {n} nodes", self.depot)))
+    }
+    Ok(())
+  }
+
+  pub fn to_tsplib(&self) -> crate::Result<Vec<u8>> {
+    self.validate()?;
+    let kind = match self.kind {
+      LatencyKind::TRP => ProblemKind::TRP,
+      LatencyKind::MLP => ProblemKind::MLP,
+    };
+    let h =
+      Problem::with(Vec::with_capacity(4096))
+        .header()
+          .problem_kind(kind)?
+          .dimension(self.costs.dimension() as u32)?;
+    Ok(
+      self.costs.write(h)?
+        .begin_depots()?
+        .write_depots(std::iter::once(self.depot))?
+        .finish()
+    )
+  }
+
+  /// Computes the latencies of a tour from LKH, rotated to start at the depot.
+  pub fn decode(&self, mut tour: Vec<Node>, report: SolveReport) -> crate::Result<LatencySolution> {
+    self.validate()?;
+    check_tour_nodes(&tour, self.costs.dimension())?;
+    match tour.iter().position(|v| *v == self.depot) {
+      Some(start) => tour.rotate_left(start),
+      None => return Err(LkhError::malformed_solution("the tour does not visit the depot")),
+    }
+
+    let mut latencies = vec![0; self.costs.dimension()];
+    let mut time = 0;
+    for w in tour.windows(2) {
+      time += self.costs.cost(w[0], w[1]);
+      latencies[w[1] as usize] = time;
+    }
+    let return_time = time + tour.last().map_or(0, |last| self.costs.cost(*last, self.depot));
+
+    Ok(LatencySolution { total_latency: latencies.iter().sum(), latencies, return_time, tour, report })
+  }
+}
+
+impl Solver {
+  /// Solves a TRP or MLP; LKH-3 only.
+  pub fn solve_minimum_latency(
+    &self,
+    problem: &MinimumLatency,
+    parameters: &Parameters
+  ) -> crate::Result<LatencySolution> {
+    let solution = self.solve_with(problem.to_tsplib()?, parameters)?;
+    problem.decode(solution.tour, solution.report)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{header::EdgeWeightKind, instance::ProblemInstance, variant::test::read_back};
+  use super::*;
+
+  #[test]
+  fn sums_latencies() {
+    let points = vec![[0., 0.], [1., 0.], [3., 0.], [6., 0.]];
+    let p = MinimumLatency::new(points.clone()).kind(LatencyKind::MLP);
+    assert_eq!(
+      read_back(p.to_tsplib()),
+      ProblemInstance::new(ProblemKind::MLP, 4).coordinates_2d(EdgeWeightKind::EUC_2D, points).depot(0)
+    );
+
+    let s = p.decode(vec![2, 3, 0, 1], SolveReport::default()).unwrap();
+    assert_eq!(s.tour, vec![0, 1, 2, 3]);
+    assert_eq!(s.latencies, vec![0, 1, 3, 6]);
+    assert_eq!((s.total_latency, s.return_time), (10, 12));
+  }
+
+  #[test]
+  fn rejects_tours_outside_the_problem() {
+    let p = MinimumLatency::new(vec![[0., 0.], [1., 0.], [3., 0.]]);
+    assert!(matches!(p.decode(vec![0, 1, 5], SolveReport::default()), Err(LkhError::MalformedTour { line: None, .. })));
+  }
+}
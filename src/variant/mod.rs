@@ -0,0 +1,44 @@
+//! Entry points for the problem kinds beyond the symmetric TSP. Each writes its problem
+//! with the section writers in [`crate::problem`], runs LKH through a [`crate::Solver`] and
+//! decodes the tour into the shape the problem asks for.
+
+pub use costs::Costs;
+
+pub mod atsp;
+pub mod costs;
+pub mod ctsp;
+pub mod gtsp;
+pub mod hcp;
+pub mod latency;
+pub mod mtsp;
+pub mod op;
+pub mod pdp;
+pub mod schedule;
+pub mod sop;
+pub mod sttsp;
+pub mod tsptw;
+pub mod vrp;
+
+/// Fails unless every node of a tour from LKH is one of the problem's `dimension` nodes.
+pub(crate) fn check_tour_nodes(tour: &[crate::Node], dimension: usize) -> crate::Result<()> {
+  match tour.iter().find(|v| **v as usize >= dimension) {
+    Some(v) => Err(crate::LkhError::malformed_solution(format!("node {v} is not one of the {dimension} nodes"))),
+    None => Ok(()),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::instance::ProblemInstance;
+
+  /// Reads a variant's problem back, so tests compare whole sections rather than searching
+  /// the text for them.
+  pub(crate) fn read_back(tsplib: crate::Result<Vec<u8>>) -> ProblemInstance {
+    String::from_utf8(tsplib.unwrap()).unwrap().parse().unwrap()
+  }
+
+  /// The lines of a section [`ProblemInstance`] keeps verbatim.
+  pub(crate) fn lines(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|l| l.to_string()).collect()
+  }
+}
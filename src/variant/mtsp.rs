@@ -0,0 +1,167 @@
+use crate::{header::ProblemKind, parameter::{MtspObjective, Parameters}, report::SolveReport, tour::parse_mtsp_solution, LkhError, Node, Problem, Solver};
+use super::{check_tour_nodes, Costs};
+
+/// Several salesmen leaving from and returning to one depot, between them visiting every
+/// other node once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mtsp {
+  costs: Costs,
+  salesmen: u32,
+  depot: Node,
+  objective: Option<MtspObjective>,
+  min_size: Option<u32>,
+  max_size: Option<u32>,
+}
+
+/// The nodes one salesman visits after leaving the depot, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SalesmanRoute {
+  /// The nodes visited, excluding the depot; empty for a salesman who stays at the depot
+  pub nodes: Vec<Node>,
+  /// The cost from the depot through `nodes` and back
+  pub length: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MtspSolution {
+  /// One route per salesman
+  pub routes: Vec<SalesmanRoute>,
+  pub report: SolveReport,
+}
+
+impl MtspSolution {
+  /// The `MINSUM` objective.
+  pub fn total_length(&self) -> i64 { self.routes.iter().map(|r| r.length).sum() }
+
+  /// The `MINMAX` objective.
+  pub fn max_length(&self) -> i64 { self.routes.iter().map(|r| r.length).max().unwrap_or(0) }
+}
+
+impl Mtsp {
+  pub fn new(costs: impl Into<Costs>, salesmen: u32) -> Self {
+    Self { costs: costs.into(), salesmen, depot: 0, objective: None, min_size: None, max_size: None }
+  }
+
+  /// The node every salesman starts and ends at; node 0 unless set.
+  pub fn depot(mut self, depot: Node) -> Self { self.depot = depot; self }
+
+  /// What to minimize; LKH-3 defaults to `MINSUM`.
+  pub fn objective(mut self, objective: MtspObjective) -> Self { self.objective = Some(objective); self }
+
+  /// The fewest nodes, excluding the depot, each salesman must visit.
+  pub fn min_size(mut self, size: u32) -> Self { self.min_size = Some(size); self }
+
+  /// The most nodes, excluding the depot, each salesman may visit.
+  pub fn max_size(mut self, size: u32) -> Self { self.max_size = Some(size); self }
+
+  pub fn validate(&self) -> crate::Result<()> {
+    let n = self.costs.dimension();
+    let customers = n.saturating_sub(1) as u64;
+    let fail = |m: String| Err(LkhError::invalid_problem(m));
+
+    if n < 3 { return fail(format!("an mTSP needs at least 3 nodes, not {n}")) }
+    if self.salesmen == 0 { return fail("an mTSP needs at least one salesman".into()) }
+    if self.depot as usize >= n { return fail(format!("depot {} is not one of the {n} nodes", self.depot)) }
+    if let Some(min) = self.min_size.filter(|min| *min as u64 * self.salesmen as u64 > customers) {
+      return fail(format!("{} salesmen cannot each visit {min} of {customers} nodes", self.salesmen))
+    }
+    if let Some(max) = self.max_size.filter(|max| (*max as u64) * (self.salesmen as u64) < customers) {
+      return fail(format!("{} salesmen visiting at most {max} nodes cannot cover {customers}", self.salesmen))
+    }
+    Ok(())
+  }
+
+  pub fn to_tsplib(&self) -> crate::Result<Vec<u8>> {
+    self.validate()?;
+    let kind = match &self.costs {
+      Costs::Explicit(m) if !m.is_symmetric() => ProblemKind::ATSP,
+      _ => ProblemKind::TSP,
+    };
+    let h =
+      Problem::with(Vec::with_capacity(4096))
+        .header()
+          .problem_kind(kind)?
+          .dimension(self.costs.dimension() as u32)?;
+    Ok(self.costs.write(h)?.finish())
+  }
+
+  /// `parameters` with this problem's `SALESMEN`, `DEPOT` and `MTSP_*` keywords.
+  pub fn parameters(&self, parameters: &Parameters) -> Parameters {
+    let mut p = parameters.clone().salesmen(self.salesmen).depot(self.depot);
+    if let Some(o) = self.objective { p = p.mtsp_objective(o); }
+    if let Some(s) = self.min_size { p = p.mtsp_min_size(s); }
+    if let Some(s) = self.max_size { p = p.mtsp_max_size(s); }
+    p
+  }
+
+  /// Measures the routes read from an `MTSP_SOLUTION_FILE`.
+  pub fn routes(&self, routes: Vec<Vec<Node>>) -> crate::Result<Vec<SalesmanRoute>> {
+    self.validate()?;
+    for nodes in &routes { check_tour_nodes(nodes, self.costs.dimension())?; }
+
+    Ok(
+      routes
+        .into_iter()
+        .map(|mut nodes| {
+          let length = match nodes.len() {
+            0 | 1 => 0,
+            _ => self.costs.path_cost(&nodes),
+          };
+          nodes.retain(|v| *v != self.depot);
+          SalesmanRoute { nodes, length }
+        })
+        .collect()
+    )
+  }
+}
+
+impl Solver {
+  /// Solves an mTSP; LKH-3 only. The routes are read from the `MTSP_SOLUTION_FILE`, which
+  /// this sets to a temporary file.
+  pub fn solve_mtsp(
+    &self,
+    problem: &Mtsp,
+    parameters: &Parameters
+  ) -> crate::Result<MtspSolution> {
+    let solution_file = self.temp_file("mtsp");
+    let parameters = problem.parameters(parameters).mtsp_solution_file(solution_file.path());
+    let solution = self.solve_with(problem.to_tsplib()?, &parameters)?;
+    let routes = parse_mtsp_solution(&std::fs::read_to_string(solution_file.path())?)?;
+    Ok(MtspSolution { routes: problem.routes(routes)?, report: solution.report })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn measures_each_salesman() {
+    let p = Mtsp::new(vec![[0., 0.], [0., 3.], [4., 3.], [0., -5.], [0., -10.]], 2)
+      .objective(MtspObjective::MINMAX)
+      .min_size(1);
+    let routes = p.routes(vec![vec![0, 1, 2, 0], vec![0, 3, 4, 0]]).unwrap();
+    assert_eq!(routes, vec![
+      SalesmanRoute { nodes: vec![1, 2], length: 3 + 4 + 5 },
+      SalesmanRoute { nodes: vec![3, 4], length: 5 + 5 + 10 },
+    ]);
+
+    let s = MtspSolution { routes, report: SolveReport::default() };
+    assert_eq!((s.total_length(), s.max_length()), (32, 20));
+
+    let mut par = vec![];
+    p.parameters(&Parameters::new()).write(&mut par).unwrap();
+    let par = String::from_utf8(par).unwrap();
+    assert!(par.contains("SALESMEN = 2\n") && par.contains("MTSP_OBJECTIVE = MINMAX\n") && par.contains("DEPOT = 1\n"));
+
+    assert!(matches!(p.routes(vec![vec![0, 1, 7, 0]]), Err(LkhError::MalformedTour { line: None, .. })));
+  }
+
+  #[test]
+  fn rejects_impossible_sizes() {
+    let p = || Mtsp::new(vec![[0., 0.]; 5], 2);
+    assert!(p().min_size(3).validate().is_err());
+    assert!(p().max_size(1).validate().is_err());
+    assert!(p().min_size(2).max_size(2).validate().is_ok());
+  }
+}
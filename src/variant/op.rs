@@ -0,0 +1,132 @@
+use crate::{header::ProblemKind, parameter::Parameters, report::SolveReport, LkhError, Node, Problem, Solution, Solver};
+use super::{check_tour_nodes, Costs};
+
+/// An orienteering problem: a tour from the depot collecting as much prize as possible
+/// without its length exceeding a budget. Not every node needs to be visited.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Op {
+  costs: Costs,
+  prizes: Vec<i64>,
+  cost_limit: i64,
+  depot: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpSolution {
+  /// The visited nodes, starting at the depot
+  pub tour: Vec<Node>,
+  /// The summed prize of the visited nodes
+  pub prize: i64,
+  /// The length of the closed tour
+  pub length: i64,
+  pub report: SolveReport,
+}
+
+impl Op {
+  /// `prizes` holds the score of every node; the depot's is not collected.
+  pub fn new(costs: impl Into<Costs>, prizes: Vec<i64>, cost_limit: i64) -> Self {
+    Self { costs: costs.into(), prizes, cost_limit, depot: 0 }
+  }
+
+  /// The node the tour starts and ends at; node 0 unless set.
+  pub fn depot(mut self, depot: Node) -> Self { self.depot = depot; self }
+
+  pub fn validate(&self) -> crate::Result<()> {
+    let n = self.costs.dimension();
+    let fail = |m: String| Err(LkhError::invalid_problem(m));
+
+    if n < 3 { return fail(format!("an OP needs at least 3 nodes, not {n}")) }
+    if self.prizes.len() != n { return fail(format!("{} prizes for {n} nodes", self.prizes.len())) }
+    if let Some((v, p)) = self.prizes.iter().enumerate().find(|(_, p)| **p < 0) {
+      return fail(format!("node {v} has the negative prize {p}"))
+    }
+    if self.cost_limit < 0 { return fail(format!("the cost limit {} is negative", self.cost_limit)) }
+    if self.depot as usize >= n { return fail(format!("depot {} is not one of the {n} nodes", self.depot)) }
+    Ok(())
+  }
+
+  pub fn to_tsplib(&self) -> crate::Result<Vec<u8>> {
+    self.validate()?;
+    let h =
+      Problem::with(Vec::with_capacity(4096))
+        .header()
+          .problem_kind(ProblemKind::OP)?
+          .dimension(self.costs.dimension() as u32)?
+          .keyword("COST_LIMIT", self.cost_limit)?;
+    Ok(
+      self.costs.write(h)?
+        .begin_node_scores()?
+        .write_node_scores(self.prizes.iter().copied())?
+        .begin_depots()?
+        .write_depots(std::iter::once(self.depot))?
+        .finish()
+    )
+  }
+
+  /// Reads the nodes LKH visits, which are those in its tour, starting at the depot.
+  pub fn decode(&self, mut tour: Vec<Node>, report: SolveReport) -> crate::Result<OpSolution> {
+    self.validate()?;
+    check_tour_nodes(&tour, self.costs.dimension())?;
+    match tour.iter().position(|v| *v == self.depot) {
+      Some(start) => tour.rotate_left(start),
+      None => return Err(LkhError::malformed_solution("the tour does not visit the depot")),
+    }
+
+    let length = self.costs.tour_cost(&tour);
+    if length > self.cost_limit {
+      let message = format!("the tour's length {length} exceeds the cost limit {}", self.cost_limit);
+      return Err(LkhError::Infeasible { message, solution: Box::new(Solution { tour, report }) })
+    }
+
+    let prize = tour[1..].iter().map(|v| self.prizes[*v as usize]).sum();
+    Ok(OpSolution { tour, prize, length, report })
+  }
+}
+
+impl Solver {
+  /// Solves an OP, failing with [`LkhError::Infeasible`] when LKH's tour is over budget.
+  /// LKH-3 only.
+  pub fn solve_op(
+    &self,
+    problem: &Op,
+    parameters: &Parameters
+  ) -> crate::Result<OpSolution> {
+    let solution = self.solve_with(problem.to_tsplib()?, parameters)?;
+    problem.decode(solution.tour, solution.report)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{header::EdgeWeightKind, instance::ProblemInstance, variant::test::{lines, read_back}};
+  use super::*;
+
+  #[test]
+  fn writes_scores_and_limit() {
+    let p = Op::new(vec![[0., 0.], [3., 0.], [3., 4.], [50., 50.]], vec![0, 5, 7, 100], 12);
+    assert_eq!(
+      read_back(p.to_tsplib()),
+      ProblemInstance::new(ProblemKind::OP, 4)
+        .keyword("COST_LIMIT", 12)
+        .coordinates_2d(EdgeWeightKind::EUC_2D, vec![[0., 0.], [3., 0.], [3., 4.], [50., 50.]])
+        .section("NODE_SCORE_SECTION", lines(&["1 0", "2 5", "3 7", "4 100"]))
+        .depot(0)
+    );
+  }
+
+  #[test]
+  fn collects_prizes_within_budget() {
+    let p = Op::new(vec![[0., 0.], [3., 0.], [3., 4.], [50., 50.]], vec![0, 5, 7, 100], 12);
+    let s = p.decode(vec![2, 0, 1], SolveReport::default()).unwrap();
+    assert_eq!((s.tour, s.prize, s.length), (vec![0, 1, 2], 12, 12));
+    assert!(matches!(p.decode(vec![0, 3], SolveReport::default()), Err(LkhError::Infeasible { .. })));
+  }
+
+  #[test]
+  fn rejects_tours_outside_the_problem() {
+    let p = Op::new(vec![[0., 0.], [3., 0.], [3., 4.], [50., 50.]], vec![0, 5, 7, 100], 12);
+    assert!(matches!(p.decode(vec![0, 1, 4], SolveReport::default()), Err(LkhError::MalformedTour { line: None, .. })));
+    let unpriced = Op::new(vec![[0., 0.], [3., 0.], [3., 4.]], vec![], 5);
+    assert!(matches!(unpriced.decode(vec![0, 1, 2], SolveReport::default()), Err(LkhError::InvalidProblem(_))));
+  }
+}
@@ -0,0 +1,246 @@
+use crate::{header::ProblemKind, node_data::PickupAndDelivery, parameter::Parameters, report::SolveReport, LkhError, Node, Problem, Solution, Solver};
+use super::{schedule::schedule, vrp::{closed_length, split_routes, Route, ScheduledRoute}, Costs};
+
+/// The latest time written for nodes without a time window.
+pub const UNBOUNDED: i64 = i32::MAX as i64;
+
+/// A pickup-and-delivery problem: goods are picked up at one node and delivered to another
+/// by the same vehicle, later on its route. With one uncapacitated vehicle this is a PDTSP,
+/// otherwise a PDPTW.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PickupDelivery {
+  kind: ProblemKind,
+  costs: Costs,
+  depot: Node,
+  capacity: Option<u32>,
+  vehicles: Option<u32>,
+  /// `(pickup, delivery, quantity)`
+  requests: Vec<(Node, Node, i64)>,
+  time_windows: Vec<[i64; 2]>,
+  service_times: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PickupDeliverySolution {
+  /// One route per vehicle used. `load` is the net change, 0 when every request is served.
+  pub routes: Vec<ScheduledRoute>,
+  /// The summed length of the routes
+  pub cost: i64,
+  pub report: SolveReport,
+}
+
+impl PickupDelivery {
+  /// A single vehicle serving every request (`TYPE: PDTSP`).
+  pub fn pdtsp(costs: impl Into<Costs>) -> Self {
+    Self::new(ProblemKind::PDTSP, costs.into(), None)
+  }
+
+  /// Vehicles of `capacity` serving the requests within time windows (`TYPE: PDPTW`).
+  pub fn pdptw(costs: impl Into<Costs>, capacity: u32) -> Self {
+    Self::new(ProblemKind::PDPTW, costs.into(), Some(capacity))
+  }
+
+  fn new(kind: ProblemKind, costs: Costs, capacity: Option<u32>) -> Self {
+    Self {
+      kind, costs, depot: 0, capacity, vehicles: None,
+      requests: vec![], time_windows: vec![], service_times: vec![]
+    }
+  }
+
+  /// Carries `quantity` from `pickup` to `delivery`. Every node takes part in at most one
+  /// request.
+  pub fn request(mut self, pickup: Node, delivery: Node, quantity: i64) -> Self {
+    self.requests.push((pickup, delivery, quantity)); self
+  }
+
+  /// The node vehicles start and end at; node 0 unless set.
+  pub fn depot(mut self, depot: Node) -> Self { self.depot = depot; self }
+
+  /// The number of vehicles available. LKH decides when this is not given.
+  pub fn vehicles(mut self, vehicles: u32) -> Self { self.vehicles = Some(vehicles); self }
+
+  /// The `[earliest, latest]` service start of every node, in order. Without them no
+  /// node has a deadline.
+  pub fn time_windows(mut self, time_windows: Vec<[i64; 2]>) -> Self { self.time_windows = time_windows; self }
+
+  /// How long serving every node takes, in order.
+  pub fn service_times(mut self, service_times: Vec<i64>) -> Self { self.service_times = service_times; self }
+
+  pub fn validate(&self) -> crate::Result<()> {
+    let n = self.costs.dimension();
+    let fail = |m: String| Err(LkhError::invalid_problem(m));
+
+    if n < 3 { return fail(format!("a {:?} needs at least 3 nodes, not {n}", self.kind)) }
+    if self.depot as usize >= n { return fail(format!("depot {} is not one of the {n} nodes", self.depot)) }
+
+    let mut used = vec![false; n];
+    used[self.depot as usize] = true;
+    for &(p, d, q) in &self.requests {
+      for v in [p, d] {
+        match used.get_mut(v as usize) {
+          None => return fail(format!("node {v} is not one of the {n} nodes")),
+          Some(true) => return fail(format!("node {v} is the depot or in more than one request")),
+          Some(u) => *u = true,
+        }
+      }
+      if q < 0 { return fail(format!("request ({p}, {d}) has the negative quantity {q}")) }
+      if let Some(c) = self.capacity.filter(|c| q > *c as i64) {
+        return fail(format!("request ({p}, {d}) carries {q}, more than the capacity {c}"))
+      }
+    }
+
+    if !self.time_windows.is_empty() && self.time_windows.len() != n {
+      return fail(format!("{} time windows for {n} nodes", self.time_windows.len()))
+    }
+    if !self.service_times.is_empty() && self.service_times.len() != n {
+      return fail(format!("{} service times for {n} nodes", self.service_times.len()))
+    }
+    Ok(())
+  }
+
+  /// Every node's line of the `PICKUP_AND_DELIVERY_SECTION`.
+  fn entries(&self) -> Vec<PickupAndDelivery> {
+    let n = self.costs.dimension();
+    let mut entries = (0..n)
+      .map(|v| PickupAndDelivery {
+        demand: 0,
+        time_window: self.time_windows.get(v).copied().unwrap_or([0, UNBOUNDED]),
+        service_time: self.service_times.get(v).copied().unwrap_or(0),
+        pickup: None,
+        delivery: None,
+      })
+      .collect::<Vec<_>>();
+
+    for &(p, d, q) in &self.requests {
+      entries[p as usize].demand = q;
+      entries[p as usize].delivery = Some(d);
+      entries[d as usize].demand = -q;
+      entries[d as usize].pickup = Some(p);
+    }
+    entries
+  }
+
+  pub fn to_tsplib(&self) -> crate::Result<Vec<u8>> {
+    self.validate()?;
+    let mut h =
+      Problem::with(Vec::with_capacity(4096))
+        .header()
+          .problem_kind(self.kind)?
+          .dimension(self.costs.dimension() as u32)?;
+    if let Some(c) = self.capacity { h = h.capacity(c)?; }
+    if let Some(v) = self.vehicles { h = h.keyword("VEHICLES", v)?; }
+
+    Ok(
+      self.costs.write(h)?
+        .begin_pickup_and_delivery()?
+        .write_pickup_and_delivery(self.entries().into_iter())?
+        .begin_depots()?
+        .write_depots(std::iter::once(self.depot))?
+        .finish()
+    )
+  }
+
+  /// Splits a tour from LKH into scheduled routes, as [`super::vrp::Cvrp::routes`].
+  pub fn routes(&self, tour: &[Node]) -> crate::Result<Vec<ScheduledRoute>> {
+    self.validate()?;
+    let n = self.costs.dimension() as Node;
+    let entries = self.entries();
+    let windows = entries.iter().map(|e| e.time_window).collect::<Vec<_>>();
+    let tour = tour.iter().map(|v| if *v >= n { self.depot } else { *v }).collect::<Vec<_>>();
+
+    Ok(
+      split_routes(&tour, |v| v == self.depot)?
+        .into_iter()
+        .map(|(depot, nodes)| {
+          let stops = nodes.iter().copied().chain([depot]).collect::<Vec<_>>();
+          let visits = schedule(&self.costs, &windows, &self.service_times, depot, windows[depot as usize][0], &stops);
+          let route = Route {
+            depot,
+            load: nodes.iter().map(|v| entries[*v as usize].demand).sum(),
+            length: closed_length(&self.costs, depot, &nodes),
+            nodes,
+          };
+          ScheduledRoute { route, visits }
+        })
+        .collect()
+    )
+  }
+
+  /// Checks that every request is picked up, then delivered later on the same route.
+  pub fn verify(&self, routes: &[ScheduledRoute]) -> Result<(), String> {
+    let mut position = vec![None; self.costs.dimension()];
+    for (r, route) in routes.iter().enumerate() {
+      for (i, v) in route.route.nodes.iter().enumerate() { position[*v as usize] = Some((r, i)); }
+    }
+
+    for &(p, d, _) in &self.requests {
+      match (position[p as usize], position[d as usize]) {
+        (Some((rp, ip)), Some((rd, id))) if rp == rd && ip < id => {},
+        (Some((rp, _)), Some((rd, _))) if rp != rd => return Err(format!("{p} and {d} are on different routes")),
+        (Some(_), Some(_)) => return Err(format!("{d} is delivered to before {p} is picked up at")),
+        _ => return Err(format!("request ({p}, {d}) is not served")),
+      }
+    }
+    Ok(())
+  }
+}
+
+impl Solver {
+  /// Solves a PDTSP or PDPTW, failing with [`LkhError::Infeasible`] when a delivery comes
+  /// before its pickup or on another route. LKH-3 only.
+  pub fn solve_pickup_delivery(
+    &self,
+    problem: &PickupDelivery,
+    parameters: &Parameters
+  ) -> crate::Result<PickupDeliverySolution> {
+    let solution = self.solve_with(problem.to_tsplib()?, parameters)?;
+    let routes = problem.routes(&solution.tour)?;
+    if let Err(message) = problem.verify(&routes) {
+      return Err(LkhError::Infeasible { message, solution: Box::new(solution) })
+    }
+
+    let Solution { report, .. } = solution;
+    Ok(PickupDeliverySolution { cost: routes.iter().map(|r| r.route.length).sum(), routes, report })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{header::EdgeWeightKind, instance::ProblemInstance, variant::test::{lines, read_back}};
+  use super::*;
+
+  #[test]
+  fn writes_pickup_and_delivery_section() {
+    let p = PickupDelivery::pdptw(vec![[0., 0.], [0., 1.], [0., 2.], [0., 3.], [0., 4.]], 10)
+      .request(1, 3, 4)
+      .request(4, 2, 6)
+      .time_windows(vec![[0, 100]; 5]);
+    assert_eq!(
+      read_back(p.to_tsplib()),
+      ProblemInstance::new(ProblemKind::PDPTW, 5)
+        .capacity(10)
+        .coordinates_2d(EdgeWeightKind::EUC_2D, vec![[0., 0.], [0., 1.], [0., 2.], [0., 3.], [0., 4.]])
+        .section("PICKUP_AND_DELIVERY_SECTION", lines(&[
+          "1 0 0 100 0 0 0", "2 4 0 100 0 0 4", "3 -6 0 100 0 5 0", "4 -4 0 100 0 2 0", "5 6 0 100 0 0 3",
+        ]))
+        .depot(0)
+    );
+  }
+
+  #[test]
+  fn verifies_pickups_precede_deliveries() {
+    let p = PickupDelivery::pdptw(vec![[0., 0.], [0., 1.], [0., 2.], [0., 3.], [0., 4.]], 10)
+      .request(1, 3, 4)
+      .request(4, 2, 6);
+    assert!(p.verify(&p.routes(&[0, 1, 4, 3, 2]).unwrap()).is_ok());
+    assert!(p.verify(&p.routes(&[0, 3, 4, 1, 2]).unwrap()).is_err());
+    // the second route is the depot copy LKH-3 numbers past the nodes
+    assert!(p.verify(&p.routes(&[0, 1, 4, 5, 3, 2]).unwrap()).is_err());
+  }
+
+  #[test]
+  fn rejects_requests_outside_the_problem() {
+    let p = PickupDelivery::pdtsp(vec![[0., 0.], [0., 1.], [0., 2.]]).request(1, 9, 1);
+    assert!(matches!(p.routes(&[0, 1, 2]), Err(LkhError::InvalidProblem(_))));
+  }
+}
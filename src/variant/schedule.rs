@@ -0,0 +1,44 @@
+use crate::Node;
+use super::Costs;
+
+/// When a vehicle reaches a node, and how that fits the node's time window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visit {
+  pub node: Node,
+  pub arrival: i64,
+  /// Time spent waiting for the window to open
+  pub wait: i64,
+  /// How much later service could have started inside the window; negative when the
+  /// window was missed
+  pub slack: i64,
+}
+
+impl Visit {
+  /// When service starts.
+  pub fn start(&self) -> i64 { self.arrival + self.wait }
+
+  pub fn is_late(&self) -> bool { self.slack < 0 }
+}
+
+/// Travels from `from`, leaving at `departure`, through `nodes`, waiting for every window
+/// to open and serving each node for its service time (none when `service_times` is empty).
+pub(crate) fn schedule(
+  costs: &Costs,
+  windows: &[[i64; 2]],
+  service_times: &[i64],
+  from: Node,
+  departure: i64,
+  nodes: &[Node]
+) -> Vec<Visit> {
+  let (mut at, mut time) = (from, departure);
+  nodes
+    .iter()
+    .map(|&v| {
+      let arrival = time + costs.cost(at, v);
+      let [open, close] = windows[v as usize];
+      let start = arrival.max(open);
+      (at, time) = (v, start + service_times.get(v as usize).copied().unwrap_or(0));
+      Visit { node: v, arrival, wait: start - arrival, slack: close - start }
+    })
+    .collect()
+}
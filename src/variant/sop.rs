@@ -0,0 +1,172 @@
+use crate::{header::{EdgeWeightFormat, EdgeWeightKind, ProblemKind}, matrix::Matrix, parameter::Parameters, report::SolveReport, LkhError, Node, Problem, Solution, Solver};
+
+/// A sequential ordering problem: the cheapest path from node 0 to the last node through
+/// every other node, in which some nodes must come before others.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sop {
+  costs: Matrix,
+  precedences: Vec<(Node, Node)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SopSolution {
+  /// Every node once, from node 0 to the last node
+  pub order: Vec<Node>,
+  /// The cost of the path `order`
+  pub cost: i64,
+  pub report: SolveReport,
+}
+
+impl Sop {
+  /// `costs.get(i, j)` is the cost of going from `i` to `j`, and must not be negative.
+  /// Node 0 comes first and the last node last, without precedences saying so.
+  pub fn new(costs: Matrix) -> Self {
+    Self { costs, precedences: vec![] }
+  }
+
+  /// Requires `before` to come somewhere before `after`.
+  pub fn precedence(mut self, before: Node, after: Node) -> Self {
+    self.precedences.push((before, after)); self
+  }
+
+  pub fn precedences(mut self, precedences: impl IntoIterator<Item = (Node, Node)>) -> Self {
+    self.precedences.extend(precedences); self
+  }
+
+  pub fn validate(&self) -> crate::Result<()> {
+    let n = self.costs.dimension();
+    let fail = |m: String| Err(LkhError::invalid_problem(m));
+
+    if n < 3 { return fail(format!("an SOP needs at least 3 nodes, not {n}")) }
+    if let Some(w) = self.costs.as_slice().iter().find(|w| **w < 0) {
+      return fail(format!("the cost {w} is negative, which SOP reserves for precedences"))
+    }
+    for &(a, b) in &self.precedences {
+      if a as usize >= n || b as usize >= n || a == b {
+        return fail(format!("({a}, {b}) is not a precedence between two of the {n} nodes"))
+      }
+      if b == 0 || a as usize == n - 1 {
+        return fail(format!("({a}, {b}) contradicts the path running from node 0 to node {}", n - 1))
+      }
+    }
+
+    // Kahn's algorithm; whatever is never freed is on a cycle
+    let mut incoming = vec![0usize; n];
+    for &(_, b) in &self.precedences { incoming[b as usize] += 1; }
+    let mut free = (0..n).filter(|v| incoming[*v] == 0).collect::<Vec<_>>();
+    let mut ordered = 0;
+    while let Some(v) = free.pop() {
+      ordered += 1;
+      for &(_, b) in self.precedences.iter().filter(|(a, _)| *a as usize == v) {
+        incoming[b as usize] -= 1;
+        if incoming[b as usize] == 0 { free.push(b as usize); }
+      }
+    }
+    if ordered < n { return fail("the precedences are cyclic".into()) }
+
+    Ok(())
+  }
+
+  /// The problem in TSPLIB format, where `-1` at row `i`, column `j` says `j` comes before `i`.
+  pub fn to_tsplib(&self) -> crate::Result<Vec<u8>> {
+    self.validate()?;
+    let n = self.costs.dimension();
+    let precedes = self.precedence_matrix();
+
+    Ok(
+      Problem::with(Vec::with_capacity(4096))
+        .header()
+          .problem_kind(ProblemKind::SOP)?
+          .dimension(n as u32)?
+          .edge_weight_kind(EdgeWeightKind::EXPLICIT)?
+          .edge_weight_format(EdgeWeightFormat::FULL_MATRIX)?
+        .coords()
+        .edge_weights()
+          .begin_edge_weights()?
+          .write_dimension(n)?
+          .write_with(EdgeWeightFormat::FULL_MATRIX, n, |i, j| match precedes[j * n + i] {
+            true => -1,
+            false => self.costs.get(i, j),
+          })?
+        .finish()
+    )
+  }
+
+  /// `m[a * n + b]` when `a` must come before `b`, including the implicit first and last nodes.
+  fn precedence_matrix(&self) -> Vec<bool> {
+    let n = self.costs.dimension();
+    let mut m = vec![false; n * n];
+    for v in 1..n {
+      m[v] = true;
+      m[(v - 1) * n + n - 1] = true;
+    }
+    for &(a, b) in &self.precedences { m[a as usize * n + b as usize] = true; }
+    m
+  }
+
+  /// Checks that `order` runs from node 0 to the last node and keeps every precedence.
+  pub fn verify(&self, order: &[Node]) -> Result<(), String> {
+    let n = self.costs.dimension();
+    let mut position = vec![usize::MAX; n];
+    for (p, v) in order.iter().enumerate() {
+      match position.get_mut(*v as usize) {
+        Some(slot) if *slot == usize::MAX => *slot = p,
+        _ => return Err(format!("node {v} is repeated or unknown")),
+      }
+    }
+    if order.len() != n { return Err(format!("{} of {n} nodes are visited", order.len())) }
+    if order.first() != Some(&0) || order.last() != Some(&(n as Node - 1)) {
+      return Err(format!("the order does not run from node 0 to node {}", n - 1))
+    }
+    match self.precedences.iter().find(|(a, b)| position[*a as usize] > position[*b as usize]) {
+      Some((a, b)) => Err(format!("node {b} comes before node {a}")),
+      None => Ok(()),
+    }
+  }
+}
+
+impl Solver {
+  /// Solves an SOP, failing with [`LkhError::Infeasible`] when LKH's order breaks a precedence.
+  pub fn solve_sop(
+    &self,
+    problem: &Sop,
+    parameters: &Parameters
+  ) -> crate::Result<SopSolution> {
+    let solution = self.solve_with(problem.to_tsplib()?, parameters)?;
+
+    let mut order = solution.tour.clone();
+    if let Some(start) = order.iter().position(|v| *v == 0) { order.rotate_left(start); }
+    if let Err(message) = problem.verify(&order) {
+      return Err(LkhError::Infeasible { message, solution: Box::new(solution) })
+    }
+
+    let Solution { report, .. } = solution;
+    Ok(SopSolution { cost: problem.costs.path_cost(&order), order, report })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{instance::ProblemInstance, variant::test::read_back};
+  use super::*;
+
+  #[test]
+  fn encodes_precedences() {
+    let weights = Matrix::from_rows(&[[0, 1, 2, 3], [-1, 5, -1, 7], [-1, 9, 10, 11], [-1, -1, -1, 15]]).unwrap();
+    let p = Sop::new(Matrix::from_fn(4, |i, j| (i * 4 + j) as i64)).precedence(2, 1);
+    assert_eq!(
+      read_back(p.to_tsplib()),
+      ProblemInstance::new(ProblemKind::SOP, 4).edge_weights(EdgeWeightFormat::FULL_MATRIX, weights)
+    );
+  }
+
+  #[test]
+  fn verifies_orders() {
+    let p = Sop::new(Matrix::from_fn(4, |i, j| (i * 4 + j) as i64)).precedence(2, 1);
+    assert!(p.verify(&[0, 2, 1, 3]).is_ok());
+    assert!(p.verify(&[0, 1, 2, 3]).is_err());
+    assert!(p.verify(&[2, 0, 1, 3]).is_err());
+    assert!(p.verify(&[0, 2, 2, 3]).is_err());
+    assert!(p.clone().precedence(1, 2).validate().is_err());
+  }
+}
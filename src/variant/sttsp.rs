@@ -0,0 +1,198 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::{header::{EdgeFormat, EdgeWeightKind, ProblemKind}, parameter::Parameters, report::SolveReport, Edge, LkhError, Node, Problem, Solver};
+
+/// A Steiner TSP: the shortest closed walk over a sparse weighted graph that visits every
+/// required node. The other (Steiner) nodes may be passed through any number of times.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sttsp {
+  dimension: u32,
+  edges: Vec<(Edge, i64)>,
+  required: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SttspSolution {
+  /// The required nodes, in the order LKH visits them
+  pub tour: Vec<Node>,
+  /// The full walk from `tour[0]` through every node passed, Steiner nodes included,
+  /// without repeating `tour[0]` at the end
+  pub walk: Vec<Node>,
+  /// The length of the closed walk
+  pub cost: i64,
+  pub report: SolveReport,
+}
+
+impl Sttsp {
+  /// The undirected graph on `dimension` nodes with `edges` as `(edge, weight)`.
+  pub fn new(dimension: u32, edges: Vec<(Edge, i64)>, required: Vec<Node>) -> Self {
+    Self { dimension, edges, required }
+  }
+
+  pub fn validate(&self) -> crate::Result<()> {
+    let n = self.dimension;
+    let fail = |m: String| Err(LkhError::invalid_problem(m));
+
+    if n < 3 { return fail(format!("an STTSP needs at least 3 nodes, not {n}")) }
+    if let Some(([a, b], w)) = self.edges.iter().find(|([a, b], w)| *a >= n || *b >= n || *w < 0) {
+      return fail(format!("edge [{a}, {b}] of weight {w} is not between two of the {n} nodes, or negative"))
+    }
+    if let Some(v) = self.required.iter().find(|v| **v >= n) {
+      return fail(format!("required node {v} is not one of the {n} nodes"))
+    }
+    if self.required.len() < 2 { return fail("an STTSP needs at least 2 required nodes".into()) }
+
+    let (distance, _) = shortest_paths(&self.adjacency(), self.required[0]);
+    match self.required.iter().find(|v| distance[**v as usize].is_none()) {
+      Some(v) => fail(format!("required node {v} cannot be reached from node {}", self.required[0])),
+      None => Ok(()),
+    }
+  }
+
+  pub fn to_tsplib(&self) -> crate::Result<Vec<u8>> {
+    self.validate()?;
+    Ok(
+      Problem::with(Vec::with_capacity(4096))
+        .header()
+          .problem_kind(ProblemKind::STTSP)?
+          .dimension(self.dimension)?
+          .edge_weight_kind(EdgeWeightKind::EXPLICIT)?
+          .edge_data_format(EdgeFormat::EDGE_LIST)?
+        .node_data()
+          .begin_required_nodes()?
+          .write_required_nodes(self.required.iter().copied())?
+        .edges()
+          .begin_adjacency()?
+          .write_weighted_edge_list(self.edges.iter().copied())?
+          .end_edge_data()?
+        .finish()
+    )
+  }
+
+  /// The neighbours of every node, with the weight of the edge to each.
+  fn adjacency(&self) -> Vec<Vec<(Node, i64)>> {
+    let mut adjacent = vec![vec![]; self.dimension as usize];
+    for &([a, b], w) in &self.edges {
+      adjacent[a as usize].push((b, w));
+      adjacent[b as usize].push((a, w));
+    }
+    adjacent
+  }
+
+  /// Expands a tour from LKH into the walk along shortest paths between its consecutive
+  /// required nodes. Steiner nodes in the tour are dropped and the walk found anew.
+  pub fn decode(&self, tour: Vec<Node>, report: SolveReport) -> crate::Result<SttspSolution> {
+    self.validate()?;
+    if let Some(v) = tour.iter().find(|v| **v >= self.dimension) {
+      return Err(LkhError::malformed_solution(format!("node {v} is not one of the {} nodes", self.dimension)))
+    }
+    let mut visits = vec![None; self.dimension as usize];
+    for v in &self.required { visits[*v as usize] = Some(0); }
+    let tour = tour.into_iter().filter(|v| visits[*v as usize].is_some()).collect::<Vec<_>>();
+    for v in &tour { visits[*v as usize] = visits[*v as usize].map(|k| k + 1); }
+    if let Some(v) = self.required.iter().find(|v| visits[**v as usize] != Some(1)) {
+      return Err(LkhError::malformed_solution(format!("the tour does not visit required node {v} exactly once")))
+    }
+
+    // one Dijkstra per required node, each used for the leg leaving it
+    let adjacent = self.adjacency();
+    let mut walk = vec![];
+    let mut cost = 0;
+
+    for (i, &from) in tour.iter().enumerate() {
+      let to = tour[(i + 1) % tour.len()];
+      let (distance, previous) = shortest_paths(&adjacent, from);
+      // validate() ensures every required node is reachable
+      cost += distance[to as usize].unwrap_or_default();
+
+      let mut path = vec![];
+      let mut v = to;
+      while v != from {
+        path.push(v);
+        v = previous[v as usize].unwrap();
+      }
+      walk.push(from);
+      walk.extend(path.into_iter().skip(1).rev());
+    }
+
+    Ok(SttspSolution { tour, walk, cost, report })
+  }
+}
+
+/// Dijkstra from `source`: the distance to, and the node before, every node on a
+/// shortest path.
+fn shortest_paths(adjacent: &[Vec<(Node, i64)>], source: Node) -> (Vec<Option<i64>>, Vec<Option<Node>>) {
+  let n = adjacent.len();
+  let mut distance = vec![None; n];
+  let mut previous = vec![None; n];
+  let mut queue = BinaryHeap::from([Reverse((0, source))]);
+  distance[source as usize] = Some(0);
+
+  while let Some(Reverse((d, v))) = queue.pop() {
+    if distance[v as usize].is_some_and(|best| d > best) { continue }
+    for &(u, w) in &adjacent[v as usize] {
+      if distance[u as usize].is_none_or(|best| d + w < best) {
+        distance[u as usize] = Some(d + w);
+        previous[u as usize] = Some(v);
+        queue.push(Reverse((d + w, u)));
+      }
+    }
+  }
+  (distance, previous)
+}
+
+impl Solver {
+  /// Solves an STTSP; LKH-3 only.
+  pub fn solve_sttsp(
+    &self,
+    problem: &Sttsp,
+    parameters: &Parameters
+  ) -> crate::Result<SttspSolution> {
+    let solution = self.solve_with(problem.to_tsplib()?, parameters)?;
+    problem.decode(solution.tour, solution.report)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{instance::ProblemInstance, variant::test::{lines, read_back}};
+  use super::*;
+
+  #[test]
+  fn writes_required_nodes_and_weighted_edges() {
+    let p = Sttsp::new(5, vec![([0, 1], 1), ([1, 2], 1), ([2, 3], 1), ([3, 4], 1), ([1, 4], 5)], vec![0, 4]);
+    let expected = ProblemInstance::new(ProblemKind::STTSP, 5)
+      .weighted_edges(vec![([0, 1], 1), ([1, 2], 1), ([2, 3], 1), ([3, 4], 1), ([1, 4], 5)])
+      .section("REQUIRED_NODES_SECTION", lines(&["1", "5", "-1"]));
+    assert_eq!(
+      read_back(p.to_tsplib()),
+      ProblemInstance { edge_weight_kind: Some(EdgeWeightKind::EXPLICIT), ..expected }
+    );
+    assert!(Sttsp::new(5, vec![([0, 1], 1)], vec![0, 4]).validate().is_err());
+  }
+
+  #[test]
+  fn expands_walk_through_steiner_nodes() {
+    //  0 --1-- 1 --1-- 2
+    //          |       |
+    //          5       1
+    //          |       |
+    //          4 --1-- 3
+    let p = Sttsp::new(5, vec![([0, 1], 1), ([1, 2], 1), ([2, 3], 1), ([3, 4], 1), ([1, 4], 5)], vec![0, 4]);
+    let s = p.decode(vec![4, 0], SolveReport::default()).unwrap();
+    assert_eq!(s.tour, vec![4, 0]);
+    assert_eq!(s.walk, vec![4, 3, 2, 1, 0, 1, 2, 3]);
+    assert_eq!(s.cost, 8);
+    // Steiner nodes LKH lists are dropped before expanding
+    let s = p.decode(vec![0, 1, 4, 3], SolveReport::default()).unwrap();
+    assert_eq!(s.walk, vec![0, 1, 2, 3, 4, 3, 2, 1]);
+  }
+
+  #[test]
+  fn rejects_tours_outside_the_graph() {
+    let p = Sttsp::new(5, vec![([0, 1], 1), ([1, 2], 1), ([2, 3], 1), ([3, 4], 1), ([1, 4], 5)], vec![0, 4]);
+    assert!(matches!(p.decode(vec![0, 4, 7], SolveReport::default()), Err(LkhError::MalformedTour { line: None, .. })));
+    assert!(p.decode(vec![0, 1], SolveReport::default()).is_err());
+    assert!(p.decode(vec![], SolveReport::default()).is_err());
+  }
+}
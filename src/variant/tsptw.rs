@@ -0,0 +1,141 @@
+use crate::{header::ProblemKind, matrix::Matrix, parameter::Parameters, report::SolveReport, LkhError, Node, Problem, Solver};
+use super::{check_tour_nodes, schedule::{schedule, Visit}, Costs};
+
+/// A single vehicle leaving the depot when its window opens and visiting every other node
+/// within that node's time window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tsptw {
+  costs: Costs,
+  time_windows: Vec<[i64; 2]>,
+  depot: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TsptwSolution {
+  /// Every node once, starting at the depot
+  pub tour: Vec<Node>,
+  /// One visit per node after the depot, then the return to the depot
+  pub visits: Vec<Visit>,
+  /// The travel time of the closed tour, not counting waits
+  pub cost: i64,
+  pub report: SolveReport,
+}
+
+impl TsptwSolution {
+  /// The visits which missed their time window.
+  pub fn violations(&self) -> impl Iterator<Item = &Visit> {
+    self.visits.iter().filter(|v| v.is_late())
+  }
+
+  /// When the vehicle is back at the depot.
+  pub fn completion_time(&self) -> i64 {
+    self.visits.last().map_or(0, |v| v.start())
+  }
+}
+
+impl Tsptw {
+  /// `travel_times.get(i, j)` is the time from `i` to `j`, which may differ from the
+  /// time back. `time_windows` holds the `[earliest, latest]` arrival at every node.
+  pub fn new(travel_times: Matrix, time_windows: Vec<[i64; 2]>) -> Self {
+    Self { costs: Costs::Explicit(travel_times), time_windows, depot: 0 }
+  }
+
+  /// The node the tour starts and ends at; node 0 unless set.
+  pub fn depot(mut self, depot: Node) -> Self { self.depot = depot; self }
+
+  pub fn validate(&self) -> crate::Result<()> {
+    let n = self.costs.dimension();
+    let fail = |m: String| Err(LkhError::invalid_problem(m));
+
+    if n < 3 { return fail(format!("a TSPTW needs at least 3 nodes, not {n}")) }
+    if self.time_windows.len() != n {
+      return fail(format!("{} time windows for {n} nodes", self.time_windows.len()))
+    }
+    if let Some((v, [a, b])) = self.time_windows.iter().enumerate().find(|(_, [a, b])| a > b) {
+      return fail(format!("node {v} has the empty time window [{a}, {b}]"))
+    }
+    if self.depot as usize >= n { return fail(format!("depot {} is not one of the {n} nodes", self.depot)) }
+    Ok(())
+  }
+
+  pub fn to_tsplib(&self) -> crate::Result<Vec<u8>> {
+    self.validate()?;
+    let h =
+      Problem::with(Vec::with_capacity(4096))
+        .header()
+          .problem_kind(ProblemKind::TSPTW)?
+          .dimension(self.costs.dimension() as u32)?;
+    Ok(
+      self.costs.write(h)?
+        .begin_time_windows()?
+        .write_time_windows(self.time_windows.iter().copied())?
+        .begin_depots()?
+        .write_depots(std::iter::once(self.depot))?
+        .finish()
+    )
+  }
+
+  /// Rotates a tour from LKH to start at the depot and schedules it.
+  pub fn decode(&self, mut tour: Vec<Node>, report: SolveReport) -> crate::Result<TsptwSolution> {
+    self.validate()?;
+    check_tour_nodes(&tour, self.costs.dimension())?;
+    let Some(start) = tour.iter().position(|v| *v == self.depot) else {
+      return Err(LkhError::malformed_solution("the tour does not visit the depot"))
+    };
+    tour.rotate_left(start);
+
+    let stops = tour[1..].iter().copied().chain([self.depot]).collect::<Vec<_>>();
+    let visits = schedule(&self.costs, &self.time_windows, &[], self.depot, self.time_windows[self.depot as usize][0], &stops);
+    let cost = self.costs.cost(self.depot, stops[0]) + self.costs.path_cost(&stops);
+    Ok(TsptwSolution { tour, visits, cost, report })
+  }
+}
+
+impl Solver {
+  /// Solves a TSPTW; LKH-3 only.
+  pub fn solve_tsptw(
+    &self,
+    problem: &Tsptw,
+    parameters: &Parameters
+  ) -> crate::Result<TsptwSolution> {
+    let solution = self.solve_with(problem.to_tsplib()?, parameters)?;
+    problem.decode(solution.tour, solution.report)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{header::EdgeWeightFormat, instance::ProblemInstance, variant::test::{lines, read_back}};
+  use super::*;
+
+  #[test]
+  fn reports_waits_and_violations() {
+    let times = Matrix::from_rows(&[[0, 5, 9], [6, 0, 3], [4, 7, 0]]).unwrap();
+    let p = Tsptw::new(times.clone(), vec![[0, 50], [8, 10], [0, 10]]);
+    assert_eq!(
+      read_back(p.to_tsplib()),
+      ProblemInstance::new(ProblemKind::TSPTW, 3)
+        .edge_weights(EdgeWeightFormat::FULL_MATRIX, times)
+        .section("TIME_WINDOW_SECTION", lines(&["1 0 50", "2 8 10", "3 0 10"]))
+        .depot(0)
+    );
+
+    let s = p.decode(vec![1, 2, 0], SolveReport::default()).unwrap();
+    assert_eq!(s.tour, vec![0, 1, 2]);
+    assert_eq!(s.visits, vec![
+      Visit { node: 1, arrival: 5, wait: 3, slack: 2 },
+      Visit { node: 2, arrival: 11, wait: 0, slack: -1 },
+      Visit { node: 0, arrival: 15, wait: 0, slack: 35 },
+    ]);
+    assert_eq!(s.violations().map(|v| v.node).collect::<Vec<_>>(), vec![2]);
+    assert_eq!((s.cost, s.completion_time()), (12, 15));
+  }
+
+  #[test]
+  fn rejects_tours_outside_the_problem() {
+    let times = Matrix::from_rows(&[[0, 5, 9], [6, 0, 3], [4, 7, 0]]).unwrap();
+    let p = Tsptw::new(times.clone(), vec![[0, 50], [8, 10], [0, 10]]);
+    assert!(matches!(p.decode(vec![0, 1, 3], SolveReport::default()), Err(LkhError::MalformedTour { line: None, .. })));
+    assert!(matches!(Tsptw::new(times, vec![[0, 50]]).decode(vec![0, 1, 2], SolveReport::default()), Err(LkhError::InvalidProblem(_))));
+  }
+}
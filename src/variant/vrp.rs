@@ -0,0 +1,644 @@
+use crate::{header::ProblemKind, node_data::NodeData, parameter::Parameters, report::SolveReport, LkhError, Node, Problem, Solver};
+use super::{schedule::{schedule, Visit}, Costs};
+
+/// A capacitated vehicle routing problem: vehicles of equal `capacity` leave a depot,
+/// serve the demand of every other node exactly once, and return (unless the problem is
+/// [`Cvrp::open`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cvrp {
+  name: Option<String>,
+  costs: Costs,
+  capacity: u32,
+  vehicles: Option<u32>,
+  demands: Vec<i64>,
+  depots: Vec<Node>,
+  open: bool,
+  distance: Option<i64>,
+}
+
+/// The nodes one vehicle serves, in order, between leaving and returning to its depot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+  pub depot: Node,
+  /// The nodes served, excluding the depot
+  pub nodes: Vec<Node>,
+  /// The total demand of `nodes`
+  pub load: i64,
+  /// The cost from the depot through `nodes` and back; open routes end at the last node
+  pub length: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CvrpSolution {
+  /// One route per vehicle used; unused vehicles have none
+  pub routes: Vec<Route>,
+  /// The summed length of the routes
+  pub cost: i64,
+  /// The problem's maximum route length, if it has one
+  pub distance_limit: Option<i64>,
+  pub report: SolveReport,
+}
+
+impl CvrpSolution {
+  /// How far each route's length is below the distance limit, in order; negative for
+  /// routes that are too long. `None` without a limit.
+  pub fn distance_slack(&self) -> Option<Vec<i64>> {
+    self.distance_limit.map(|limit| self.routes.iter().map(|r| limit - r.length).collect())
+  }
+
+  /// The routes longer than the distance limit.
+  pub fn over_distance(&self) -> impl Iterator<Item = &Route> {
+    self.routes.iter().filter(|r| self.distance_limit.is_some_and(|limit| r.length > limit))
+  }
+}
+
+impl Cvrp {
+  pub fn new(costs: impl Into<Costs>, capacity: u32) -> Self {
+    Self {
+      name: None, costs: costs.into(), capacity, vehicles: None, demands: vec![], depots: vec![],
+      open: false, distance: None
+    }
+  }
+
+  pub fn name(mut self, name: impl Into<String>) -> Self { self.name = Some(name.into()); self }
+
+  /// The demand of every node, in order; depots should have demand 0.
+  pub fn demands(mut self, demands: Vec<i64>) -> Self { self.demands = demands; self }
+
+  /// Adds a depot. Without any, node 0 is the depot.
+  pub fn depot(mut self, depot: Node) -> Self { self.depots.push(depot); self }
+
+  /// The number of vehicles available. LKH decides when this is not given.
+  pub fn vehicles(mut self, vehicles: u32) -> Self { self.vehicles = Some(vehicles); self }
+
+  /// Vehicles end their routes at their last node instead of returning (`TYPE: OVRP`).
+  pub fn open(mut self) -> Self { self.open = true; self }
+
+  /// The longest a route may be (`DISTANCE`); without `open`, this makes a DCVRP.
+  pub fn distance_limit(mut self, distance: i64) -> Self { self.distance = Some(distance); self }
+
+  /// The problem type this is written as.
+  pub fn kind(&self) -> ProblemKind {
+    match (self.open, self.distance) {
+      (true, _) => ProblemKind::OVRP,
+      (false, Some(_)) => ProblemKind::DCVRP,
+      (false, None) => ProblemKind::CVRP,
+    }
+  }
+
+  pub fn costs(&self) -> &Costs { &self.costs }
+
+  pub fn capacity(&self) -> u32 { self.capacity }
+
+  pub fn depots(&self) -> &[Node] {
+    match self.depots.is_empty() {
+      true => &[0],
+      false => &self.depots,
+    }
+  }
+
+  pub fn validate(&self) -> crate::Result<()> {
+    let n = self.costs.dimension();
+    let fail = |m: String| Err(LkhError::invalid_problem(m));
+
+    if n == 0 { return fail("a CVRP needs at least one node".into()) }
+    // LKH-3 solves the VRP types as symmetric, so routes measured one way would disagree with it
+    if let Costs::Explicit(m) = &self.costs {
+      if !m.is_symmetric() { return fail(format!("a {:?} needs symmetric costs", self.kind())) }
+    }
+    if self.demands.len() != n {
+      return fail(format!("{} demands for {n} nodes", self.demands.len()))
+    }
+    if let Some(d) = self.depots().iter().find(|d| **d as usize >= n) {
+      return fail(format!("depot {d} is not one of the {n} nodes"))
+    }
+    if let Some((v, d)) = self.demands.iter().enumerate().find(|(_, d)| **d > self.capacity as i64) {
+      return fail(format!("node {v} demands {d}, more than the capacity {}", self.capacity))
+    }
+    if self.vehicles == Some(0) { return fail("a CVRP needs at least one vehicle".into()) }
+    if let Some(d) = self.distance.filter(|d| *d <= 0) {
+      return fail(format!("the distance limit {d} is not positive"))
+    }
+
+    Ok(())
+  }
+
+  /// The problem in TSPLIB format, as LKH-3 reads it.
+  pub fn to_tsplib(&self) -> crate::Result<Vec<u8>> {
+    self.validate()?;
+    self.write_problem(self.kind(), Ok)
+  }
+
+  /// Writes the sections every VRP shares, then those `sections` adds.
+  pub(crate) fn write_problem(
+    &self,
+    kind: ProblemKind,
+    sections: impl FnOnce(NodeData<Vec<u8>>) -> crate::Result<NodeData<Vec<u8>>>
+  ) -> crate::Result<Vec<u8>> {
+    let mut h = Problem::with(Vec::with_capacity(4096)).header();
+    if let Some(name) = &self.name { h = h.name(name)?; }
+    h = h
+      .problem_kind(kind)?
+      .dimension(self.costs.dimension() as u32)?
+      .capacity(self.capacity)?;
+    if let Some(v) = self.vehicles { h = h.keyword("VEHICLES", v)?; }
+    if let Some(d) = self.distance { h = h.keyword("DISTANCE", d)?; }
+
+    let d =
+      self.costs.write(h)?
+        .begin_demands()?
+        .write_demands(self.demands.iter().copied())?
+        .begin_depots()?
+        .write_depots(self.depots().iter().copied())?;
+    Ok(sections(d)?.finish())
+  }
+
+  /// Splits a tour from LKH into routes. LKH-3 represents the extra vehicles by copies of
+  /// the depot numbered past the problem's nodes, which are read as the first depot.
+  pub fn routes(&self, tour: &[Node]) -> crate::Result<Vec<Route>> {
+    self.validate()?;
+    let n = self.costs.dimension() as Node;
+    let depots = self.depots();
+    let tour = tour.iter().map(|v| if *v >= n { depots[0] } else { *v }).collect::<Vec<_>>();
+
+    Ok(
+      split_routes(&tour, |v| depots.contains(&v))?
+        .into_iter()
+        .map(|(depot, nodes)| Route {
+          depot,
+          load: nodes.iter().map(|v| self.demands[*v as usize]).sum(),
+          length: match self.open {
+            true => open_length(&self.costs, depot, &nodes),
+            false => closed_length(&self.costs, depot, &nodes),
+          },
+          nodes,
+        })
+        .collect()
+    )
+  }
+}
+
+/// A CVRP where every node must be served within a time window. The costs are travel times.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cvrptw {
+  cvrp: Cvrp,
+  time_windows: Vec<[i64; 2]>,
+  service_times: Vec<i64>,
+}
+
+/// A [`Route`] with the time each node is reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledRoute {
+  pub route: Route,
+  /// One visit per node of the route, then the return to the depot. Vehicles leave the
+  /// depot when its window opens.
+  pub visits: Vec<Visit>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CvrptwSolution {
+  pub routes: Vec<ScheduledRoute>,
+  /// The summed length of the routes
+  pub cost: i64,
+  pub report: SolveReport,
+}
+
+impl Cvrptw {
+  /// `time_windows` holds the `[earliest, latest]` service start of every node; the
+  /// depot's window is the working day.
+  pub fn new(cvrp: Cvrp, time_windows: Vec<[i64; 2]>) -> Self {
+    Self { cvrp, time_windows, service_times: vec![] }
+  }
+
+  /// How long serving every node takes, in order.
+  pub fn service_times(mut self, service_times: Vec<i64>) -> Self { self.service_times = service_times; self }
+
+  pub fn validate(&self) -> crate::Result<()> {
+    self.cvrp.validate()?;
+    let n = self.cvrp.costs.dimension();
+    let fail = |m: String| Err(LkhError::invalid_problem(m));
+
+    if self.time_windows.len() != n {
+      return fail(format!("{} time windows for {n} nodes", self.time_windows.len()))
+    }
+    if let Some((v, [a, b])) = self.time_windows.iter().enumerate().find(|(_, [a, b])| a > b) {
+      return fail(format!("node {v} has the empty time window [{a}, {b}]"))
+    }
+    if !self.service_times.is_empty() && self.service_times.len() != n {
+      return fail(format!("{} service times for {n} nodes", self.service_times.len()))
+    }
+    Ok(())
+  }
+
+  pub fn to_tsplib(&self) -> crate::Result<Vec<u8>> {
+    self.validate()?;
+    self.cvrp.write_problem(ProblemKind::CVRPTW, |mut d| {
+      d = d.begin_time_windows()?.write_time_windows(self.time_windows.iter().copied())?;
+      if !self.service_times.is_empty() {
+        d = d.begin_service_times()?.write_service_times(self.service_times.iter().copied())?;
+      }
+      Ok(d)
+    })
+  }
+
+  /// Splits a tour from LKH into routes, as [`Cvrp::routes`], and schedules each.
+  pub fn routes(&self, tour: &[Node]) -> crate::Result<Vec<ScheduledRoute>> {
+    self.validate()?;
+    Ok(
+      self.cvrp.routes(tour)?
+        .into_iter()
+        .map(|route| {
+          let depot = route.depot;
+          let stops = route.nodes.iter().copied().chain([depot]).collect::<Vec<_>>();
+          let visits = schedule(
+            &self.cvrp.costs, &self.time_windows, &self.service_times,
+            depot, self.time_windows[depot as usize][0], &stops
+          );
+          ScheduledRoute { route, visits }
+        })
+        .collect()
+    )
+  }
+}
+
+/// A CVRP whose vehicles first deliver to linehaul nodes, then collect from backhaul nodes.
+/// The demand of a backhaul node is the amount collected there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vrpb {
+  cvrp: Cvrp,
+  backhauls: Vec<Node>,
+}
+
+/// A CVRP where every node both receives a delivery and hands over a pickup, in one visit.
+/// The demands of the [`Cvrp`] are the deliveries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vrpspd {
+  cvrp: Cvrp,
+  pickups: Vec<i64>,
+}
+
+/// A [`Route`] with what its vehicle carries along the way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedRoute {
+  pub route: Route,
+  /// The load leaving the depot, then after serving each node of the route
+  pub loads: Vec<i64>,
+  /// Whether the load exceeds the capacity anywhere on the route
+  pub over_capacity: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedSolution {
+  pub routes: Vec<LoadedRoute>,
+  /// The summed length of the routes
+  pub cost: i64,
+  pub report: SolveReport,
+}
+
+impl LoadedSolution {
+  /// The routes whose load exceeds the capacity somewhere.
+  pub fn over_capacity(&self) -> impl Iterator<Item = &LoadedRoute> {
+    self.routes.iter().filter(|r| r.over_capacity)
+  }
+}
+
+impl LoadedRoute {
+  /// The vehicle starts with everything it delivers, then loses each delivery and gains
+  /// each pickup.
+  fn new(route: Route, capacity: u32, delivery: impl Fn(Node) -> i64, pickup: impl Fn(Node) -> i64) -> Self {
+    let mut load = route.nodes.iter().map(|v| delivery(*v)).sum::<i64>();
+    let mut loads = vec![load];
+    for &v in &route.nodes {
+      load += pickup(v) - delivery(v);
+      loads.push(load);
+    }
+    let over_capacity = loads.iter().any(|l| *l > capacity as i64);
+    Self { route, loads, over_capacity }
+  }
+
+  pub fn peak_load(&self) -> i64 { self.loads.iter().copied().max().unwrap_or(0) }
+}
+
+impl Vrpb {
+  pub fn new(cvrp: Cvrp, backhauls: Vec<Node>) -> Self { Self { cvrp, backhauls } }
+
+  pub fn validate(&self) -> crate::Result<()> {
+    self.cvrp.validate()?;
+    let n = self.cvrp.costs.dimension();
+    match self.backhauls.iter().find(|v| **v as usize >= n || self.cvrp.depots().contains(v)) {
+      Some(v) => Err(LkhError::invalid_problem(format!("backhaul {v} is a depot or not one of the {n} nodes"))),
+      None => Ok(()),
+    }
+  }
+
+  pub fn to_tsplib(&self) -> crate::Result<Vec<u8>> {
+    self.validate()?;
+    self.cvrp.write_problem(ProblemKind::VRPB, |d| {
+      d.begin_backhauls()?.write_backhauls(self.backhauls.iter().copied())
+    })
+  }
+
+  /// Splits a tour from LKH into routes with their loads.
+  pub fn routes(&self, tour: &[Node]) -> crate::Result<Vec<LoadedRoute>> {
+    self.validate()?;
+    let n = self.cvrp.costs.dimension();
+    let mut is_backhaul = vec![false; n];
+    for v in &self.backhauls { is_backhaul[*v as usize] = true; }
+    let demand = |v: Node| self.cvrp.demands[v as usize];
+
+    Ok(
+      self.cvrp.routes(tour)?
+        .into_iter()
+        .map(|route| LoadedRoute::new(
+          route, self.cvrp.capacity,
+          |v| if is_backhaul[v as usize] { 0 } else { demand(v) },
+          |v| if is_backhaul[v as usize] { demand(v) } else { 0 },
+        ))
+        .collect()
+    )
+  }
+
+  /// Checks that no route serves a linehaul node after a backhaul node.
+  pub fn verify(&self, routes: &[LoadedRoute]) -> Result<(), String> {
+    let backhaul = |v: &Node| self.backhauls.contains(v);
+    match routes.iter().flat_map(|r| r.route.nodes.windows(2)).find(|w| backhaul(&w[0]) && !backhaul(&w[1])) {
+      Some(w) => Err(format!("linehaul {} is served after backhaul {}", w[1], w[0])),
+      None => Ok(()),
+    }
+  }
+}
+
+impl Vrpspd {
+  /// `pickups` holds the amount collected at every node, in order.
+  pub fn new(cvrp: Cvrp, pickups: Vec<i64>) -> Self { Self { cvrp, pickups } }
+
+  pub fn validate(&self) -> crate::Result<()> {
+    self.cvrp.validate()?;
+    let n = self.cvrp.costs.dimension();
+    let fail = |m: String| Err(LkhError::invalid_problem(m));
+
+    if self.pickups.len() != n { return fail(format!("{} pickups for {n} nodes", self.pickups.len())) }
+    if let Some((v, p)) = self.pickups.iter().enumerate().find(|(_, p)| **p > self.cvrp.capacity as i64) {
+      return fail(format!("node {v} has a pickup of {p}, more than the capacity {}", self.cvrp.capacity))
+    }
+    Ok(())
+  }
+
+  pub fn to_tsplib(&self) -> crate::Result<Vec<u8>> {
+    self.validate()?;
+    self.cvrp.write_problem(ProblemKind::VRPSPD, |d| {
+      let amounts = self.pickups.iter().zip(&self.cvrp.demands).map(|(p, d)| [*p, *d]);
+      d.begin_pickup_and_delivery()?.write_pickup_and_delivery_amounts(amounts)
+    })
+  }
+
+  /// Splits a tour from LKH into routes with their loads.
+  pub fn routes(&self, tour: &[Node]) -> crate::Result<Vec<LoadedRoute>> {
+    self.validate()?;
+    Ok(
+      self.cvrp.routes(tour)?
+        .into_iter()
+        .map(|route| LoadedRoute::new(
+          route, self.cvrp.capacity,
+          |v| self.cvrp.demands[v as usize],
+          |v| self.pickups[v as usize],
+        ))
+        .collect()
+    )
+  }
+}
+
+impl Solver {
+  /// Solves a CVRP. This needs LKH-3; LKH-2 does not read `TYPE: CVRP`.
+  pub fn solve_cvrp(
+    &self,
+    problem: &Cvrp,
+    parameters: &Parameters
+  ) -> crate::Result<CvrpSolution> {
+    let solution = self.solve_with(problem.to_tsplib()?, parameters)?;
+    let routes = problem.routes(&solution.tour)?;
+    Ok(CvrpSolution {
+      cost: routes.iter().map(|r| r.length).sum(),
+      routes,
+      distance_limit: problem.distance,
+      report: solution.report
+    })
+  }
+
+  /// Solves a CVRPTW; LKH-3 only.
+  pub fn solve_cvrptw(
+    &self,
+    problem: &Cvrptw,
+    parameters: &Parameters
+  ) -> crate::Result<CvrptwSolution> {
+    let solution = self.solve_with(problem.to_tsplib()?, parameters)?;
+    let routes = problem.routes(&solution.tour)?;
+    Ok(CvrptwSolution { cost: routes.iter().map(|r| r.route.length).sum(), routes, report: solution.report })
+  }
+}
+
+impl Solver {
+  /// Solves a VRPB, failing with [`LkhError::Infeasible`] when a route serves a linehaul
+  /// node after a backhaul node. LKH-3 only.
+  pub fn solve_vrpb(
+    &self,
+    problem: &Vrpb,
+    parameters: &Parameters
+  ) -> crate::Result<LoadedSolution> {
+    let solution = self.solve_with(problem.to_tsplib()?, parameters)?;
+    let routes = problem.routes(&solution.tour)?;
+    if let Err(message) = problem.verify(&routes) {
+      return Err(LkhError::Infeasible { message, solution: Box::new(solution) })
+    }
+    Ok(LoadedSolution { cost: routes.iter().map(|r| r.route.length).sum(), routes, report: solution.report })
+  }
+
+  /// Solves a VRPSPD; LKH-3 only.
+  pub fn solve_vrpspd(
+    &self,
+    problem: &Vrpspd,
+    parameters: &Parameters
+  ) -> crate::Result<LoadedSolution> {
+    let solution = self.solve_with(problem.to_tsplib()?, parameters)?;
+    let routes = problem.routes(&solution.tour)?;
+    Ok(LoadedSolution { cost: routes.iter().map(|r| r.route.length).sum(), routes, report: solution.report })
+  }
+}
+
+/// Splits a closed tour at every depot into `(depot, nodes)` pairs, skipping empty routes.
+pub(crate) fn split_routes(
+  tour: &[Node],
+  is_depot: impl Fn(Node) -> bool
+) -> crate::Result<Vec<(Node, Vec<Node>)>> {
+  let Some(start) = tour.iter().position(|v| is_depot(*v)) else {
+    return Err(LkhError::malformed_solution("the tour visits no depot"))
+  };
+
+  let mut routes: Vec<(Node, Vec<Node>)> = vec![];
+  for v in tour[start..].iter().chain(&tour[..start]).copied() {
+    match is_depot(v) {
+      true => routes.push((v, vec![])),
+      false => routes.last_mut().unwrap().1.push(v),
+    }
+  }
+  routes.retain(|(_, nodes)| !nodes.is_empty());
+  Ok(routes)
+}
+
+/// The cost from `depot` through `nodes` and back to `depot`.
+pub(crate) fn closed_length(costs: &Costs, depot: Node, nodes: &[Node]) -> i64 {
+  match (nodes.first(), nodes.last()) {
+    (Some(&first), Some(&last)) => costs.cost(depot, first) + costs.path_cost(nodes) + costs.cost(last, depot),
+    _ => 0,
+  }
+}
+
+/// The cost from `depot` through `nodes`, ending at the last.
+pub(crate) fn open_length(costs: &Costs, depot: Node, nodes: &[Node]) -> i64 {
+  nodes.first().map_or(0, |first| costs.cost(depot, *first) + costs.path_cost(nodes))
+}
+
+#[cfg(test)]
+mod test {
+  use crate::{header::EdgeWeightKind, instance::ProblemInstance, matrix::Matrix, variant::test::{lines, read_back}};
+  use super::*;
+
+  #[test]
+  fn writes_vrp_sections() {
+    let points = vec![[0., 0.], [0., 10.], [10., 10.], [0., -10.], [-10., -10.]];
+    assert_eq!(
+      read_back(Cvrp::new(points.clone(), 20).demands(vec![0, 5, 10, 7, 8]).vehicles(2).to_tsplib()),
+      ProblemInstance::new(ProblemKind::CVRP, 5)
+        .capacity(20)
+        .keyword("VEHICLES", 2)
+        .coordinates_2d(EdgeWeightKind::EUC_2D, points)
+        .demands(vec![0, 5, 10, 7, 8])
+        .depot(0)
+    );
+  }
+
+  #[test]
+  fn splits_routes_on_depot_copies() {
+    let cvrp = Cvrp::new(vec![[0., 0.], [0., 10.], [10., 10.], [0., -10.], [-10., -10.]], 20)
+      .demands(vec![0, 5, 10, 7, 8])
+      .vehicles(2);
+    // LKH-3 numbers the second vehicle's depot 6
+    let routes = cvrp.routes(&[1, 2, 5, 3, 4, 0]).unwrap();
+    assert_eq!(routes, vec![
+      Route { depot: 0, nodes: vec![3, 4], load: 15, length: 10 + 10 + 14 },
+      Route { depot: 0, nodes: vec![1, 2], load: 15, length: 10 + 10 + 14 },
+    ]);
+  }
+
+  #[test]
+  fn schedules_time_windows() {
+    let points = vec![[0., 0.], [0., 10.], [10., 10.], [0., -10.], [-10., -10.]];
+    let cvrp = Cvrp::new(points.clone(), 20).demands(vec![0, 5, 10, 7, 8]).vehicles(2);
+    let p = Cvrptw::new(cvrp, vec![[0, 100], [15, 20], [0, 25], [0, 100], [0, 5]])
+      .service_times(vec![0, 2, 2, 2, 2]);
+    assert_eq!(
+      read_back(p.to_tsplib()),
+      ProblemInstance::new(ProblemKind::CVRPTW, 5)
+        .capacity(20)
+        .keyword("VEHICLES", 2)
+        .coordinates_2d(EdgeWeightKind::EUC_2D, points)
+        .demands(vec![0, 5, 10, 7, 8])
+        .depot(0)
+        .section("TIME_WINDOW_SECTION", lines(&["1 0 100", "2 15 20", "3 0 25", "4 0 100", "5 0 5"]))
+        .section("SERVICE_TIME_SECTION", lines(&["1 0", "2 2", "3 2", "4 2", "5 2"]))
+    );
+
+    let routes = p.routes(&[0, 1, 2, 5, 3, 4]).unwrap();
+    assert_eq!(routes[0].route.nodes, vec![1, 2]);
+    assert_eq!(routes[0].visits, vec![
+      Visit { node: 1, arrival: 10, wait: 5, slack: 5 },
+      Visit { node: 2, arrival: 27, wait: 0, slack: -2 },
+      Visit { node: 0, arrival: 43, wait: 0, slack: 57 },
+    ]);
+    assert!(routes[1].visits[1].is_late());
+  }
+
+  #[test]
+  fn measures_open_and_limited_routes() {
+    let points = vec![[0., 0.], [0., 10.], [10., 10.], [0., -10.], [-10., -10.]];
+    let cvrp = Cvrp::new(points.clone(), 20).demands(vec![0, 5, 10, 7, 8]).vehicles(2);
+    let p = cvrp.clone().open().distance_limit(25);
+    assert_eq!(
+      read_back(p.to_tsplib()),
+      ProblemInstance::new(ProblemKind::OVRP, 5)
+        .capacity(20)
+        .keyword("VEHICLES", 2)
+        .keyword("DISTANCE", 25)
+        .coordinates_2d(EdgeWeightKind::EUC_2D, points)
+        .demands(vec![0, 5, 10, 7, 8])
+        .depot(0)
+    );
+    assert_eq!(cvrp.clone().distance_limit(25).kind(), ProblemKind::DCVRP);
+
+    let routes = p.routes(&[0, 1, 2, 5, 4, 3]).unwrap();
+    assert_eq!(routes.iter().map(|r| r.length).collect::<Vec<_>>(), vec![20, 14 + 10]);
+
+    let routes = cvrp.routes(&[0, 1, 2, 5, 4, 3]).unwrap();
+    let s = CvrpSolution { cost: 0, routes, distance_limit: Some(25), report: SolveReport::default() };
+    assert_eq!(s.distance_slack(), Some(vec![25 - 34, 25 - 34]));
+    assert_eq!(s.over_distance().count(), 2);
+  }
+
+  #[test]
+  fn tracks_backhaul_loads() {
+    let points = vec![[0., 0.], [0., 10.], [10., 10.], [0., -10.], [-10., -10.]];
+    let cvrp = Cvrp::new(points.clone(), 20).demands(vec![0, 5, 10, 7, 8]).vehicles(2);
+    let p = Vrpb::new(cvrp, vec![2, 4]);
+    assert_eq!(
+      read_back(p.to_tsplib()),
+      ProblemInstance::new(ProblemKind::VRPB, 5)
+        .capacity(20)
+        .keyword("VEHICLES", 2)
+        .coordinates_2d(EdgeWeightKind::EUC_2D, points)
+        .demands(vec![0, 5, 10, 7, 8])
+        .depot(0)
+        .section("BACKHAUL_SECTION", lines(&["3", "5", "-1"]))
+    );
+
+    let routes = p.routes(&[0, 1, 2, 5, 3, 4]).unwrap();
+    assert_eq!(routes[0].loads, vec![5, 0, 10]);
+    assert_eq!(routes[1].loads, vec![7, 0, 8]);
+    assert!(routes.iter().all(|r| !r.over_capacity));
+    assert!(p.verify(&routes).is_ok());
+    assert!(p.verify(&p.routes(&[0, 2, 1, 5, 3, 4]).unwrap()).is_err());
+  }
+
+  #[test]
+  fn flags_overloaded_pickups() {
+    let points = vec![[0., 0.], [0., 10.], [10., 10.], [0., -10.], [-10., -10.]];
+    let cvrp = Cvrp::new(points.clone(), 20).demands(vec![0, 5, 10, 7, 8]).vehicles(2);
+    let p = Vrpspd::new(cvrp, vec![0, 12, 9, 0, 3]);
+    assert_eq!(
+      read_back(p.to_tsplib()),
+      ProblemInstance::new(ProblemKind::VRPSPD, 5)
+        .capacity(20)
+        .keyword("VEHICLES", 2)
+        .coordinates_2d(EdgeWeightKind::EUC_2D, points)
+        .demands(vec![0, 5, 10, 7, 8])
+        .depot(0)
+        .section("PICKUP_AND_DELIVERY_SECTION", lines(&[
+          "1 0 0 2147483647 0 0 0", "2 5 0 2147483647 0 12 5", "3 10 0 2147483647 0 9 10",
+          "4 7 0 2147483647 0 0 7", "5 8 0 2147483647 0 3 8",
+        ]))
+    );
+
+    let routes = p.routes(&[0, 1, 2, 5, 3, 4]).unwrap();
+    assert_eq!((routes[0].loads.clone(), routes[0].over_capacity, routes[0].peak_load()), (vec![15, 22, 21], true, 22));
+    assert_eq!((routes[1].loads.clone(), routes[1].over_capacity), (vec![15, 8, 3], false));
+  }
+
+  #[test]
+  fn rejects_oversized_demands() {
+    let cvrp = Cvrp::new(vec![[0., 0.], [0., 10.], [10., 10.], [0., -10.], [-10., -10.]], 20);
+    assert!(cvrp.clone().demands(vec![0, 5, 30, 7, 8]).validate().is_err());
+    assert!(cvrp.clone().demands(vec![0, 5]).validate().is_err());
+    let one_way = Matrix::from_rows(&[[0, 1, 2], [5, 0, 3], [2, 3, 0]]).unwrap();
+    assert!(Cvrp::new(one_way, 10).demands(vec![0, 1, 1]).validate().is_err());
+    assert!(matches!(cvrp.demands(vec![0, 5]).routes(&[0, 1, 2, 3, 4]), Err(LkhError::InvalidProblem(_))));
+  }
+}